@@ -0,0 +1,140 @@
+//! Renders a map the way the module would seed it, without a running database.
+//!
+//! ```text
+//! map-render [--world <name> | --tiled <file> | --procedural <seed>] [--out <dir>] [--scale <pixels>]
+//! ```
+//!
+//! Without a source the draconis world is rendered; `--procedural` runs alpha's terrain settings with another seed.
+//! Every floor is written as `z<level>.txt` and `z<level>.png` in the output directory.
+
+mod render;
+mod store;
+
+use crate::{
+    render::{Floor, render_ascii, render_png},
+    store::MemoryMap,
+};
+use ikariadb_core::{
+    MapSource, WorldConfig,
+    repository::world::{
+        terrain::TerrainSettings,
+        tiled::parse_tiled_map,
+        types::{MapBlueprint, Vec3},
+    },
+    worlds::{self, ALPHA_TERRAIN, WORLD_NAMES},
+};
+use std::{fs, path::PathBuf, process::ExitCode};
+
+const DEFAULT_OUT_DIR: &str = "map-render-out";
+const DEFAULT_SCALE: usize = 2;
+
+enum Source {
+    World(WorldConfig),
+    Tiled(PathBuf),
+    Procedural(u64),
+}
+
+struct Args {
+    source: Source,
+    out: PathBuf,
+    scale: usize,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        source: Source::World(worlds::draconis()),
+        out: PathBuf::from(DEFAULT_OUT_DIR),
+        scale: DEFAULT_SCALE,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("Missing value for {arg}"));
+        match arg.as_str() {
+            "--world" => {
+                let name = value()?;
+                parsed.source = Source::World(
+                    worlds::by_name(&name)
+                        .ok_or_else(|| format!("Unknown world: {name} (expected one of {})", WORLD_NAMES.join(", ")))?,
+                );
+            },
+            "--tiled" => parsed.source = Source::Tiled(PathBuf::from(value()?)),
+            "--procedural" => {
+                let seed = value()?;
+                parsed.source = Source::Procedural(seed.parse().map_err(|_| format!("Invalid seed: {seed}"))?);
+            },
+            "--out" => parsed.out = PathBuf::from(value()?),
+            "--scale" => {
+                let scale = value()?;
+                parsed.scale = scale
+                    .parse()
+                    .ok()
+                    .filter(|scale| *scale > 0)
+                    .ok_or_else(|| format!("Invalid scale: {scale}"))?;
+            },
+            _ => return Err(format!("Unknown argument: {arg}")),
+        }
+    }
+    Ok(parsed)
+}
+
+fn load(source: &Source) -> Result<MemoryMap, String> {
+    let (blueprint, spawn) = match source {
+        Source::World(config) => seeded(config.map_source, config.spawn)?,
+        Source::Tiled(path) => {
+            let source = fs::read_to_string(path).map_err(|err| format!("Cannot read {}: {err}", path.display()))?;
+            let blueprint = parse_tiled_map(&source).map_err(|err| err.to_string())?;
+            (blueprint, WorldConfig::default().spawn)
+        },
+        Source::Procedural(seed) => {
+            let settings = TerrainSettings {
+                seed: *seed,
+                ..ALPHA_TERRAIN
+            };
+            seeded(MapSource::Procedural(settings), WorldConfig::default().spawn)?
+        },
+    };
+    Ok(MemoryMap::from_blueprint(&blueprint, spawn))
+}
+
+/// Blueprint of `source` and the spawn `init` would settle on for it.
+fn seeded(source: MapSource, spawn: Vec3) -> Result<(MapBlueprint, Vec3), String> {
+    let (blueprint, generated_spawn) = source.blueprint().map_err(|err| err.to_string())?;
+    Ok((blueprint, generated_spawn.unwrap_or(spawn)))
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let map = load(&args.source)?;
+    fs::create_dir_all(&args.out).map_err(|err| format!("Cannot create {}: {err}", args.out.display()))?;
+
+    for z in map.floors() {
+        let Some(floor) = Floor::new(&map, z) else {
+            continue;
+        };
+        let ascii_path = args.out.join(format!("z{z}.txt"));
+        fs::write(&ascii_path, render_ascii(&map, &floor))
+            .map_err(|err| format!("Cannot write {}: {err}", ascii_path.display()))?;
+        let png_path = args.out.join(format!("z{z}.png"));
+        render_png(&map, &floor, args.scale, &png_path)?;
+
+        println!(
+            "z={z}: {} rows, {} floor links, {} regions, {}x{} tiles -> {}, {}",
+            map.rows_on(z).count(),
+            map.links.iter().filter(|link| Vec3::from_map_id(link.map_id).z == z).count(),
+            map.regions_on(z).count(),
+            floor.width(),
+            floor.height(),
+            ascii_path.display(),
+            png_path.display()
+        );
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match parse_args(std::env::args().skip(1)).and_then(run) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        },
+    }
+}
@@ -0,0 +1,267 @@
+use crate::store::MemoryMap;
+use ikariadb_core::{
+    constants::SECTOR_SIZE,
+    repository::world::types::{MapTileStackV1, MapTileV1, Rect, RegionFlagsV1, Vec2},
+};
+use std::{fs::File, io::BufWriter, path::Path};
+
+type Rgb = [u8; 3];
+
+const SPAWN_COLOR: Rgb = [255, 0, 255];
+const TEMPLE_COLOR: Rgb = [255, 215, 0];
+
+pub const ASCII_LEGEND: &str = "\
+~ water  . grass  : sand  , dirt  _ stone floor  = road  % swamp  * snow
+\" border  T tree  b bush  o stone  # wall  < stair up  H ladder  O hole  + door
+@ spawn  A temple  | and - sector boundaries; protection zones are lighter in the PNG";
+
+/// Rasterized floor: the topmost tile of each cell, or `None` where the floor has no map.
+pub struct Floor<'a> {
+    pub z: u8,
+    pub bounds: Rect,
+    cells: Vec<Option<&'a MapTileStackV1>>,
+}
+
+impl<'a> Floor<'a> {
+    pub fn new(map: &'a MemoryMap, z: u8) -> Option<Self> {
+        let bounds = map.bounds(z)?;
+        let width = (bounds.max.x - bounds.min.x) as usize + 1;
+        let height = (bounds.max.y - bounds.min.y) as usize + 1;
+        let mut cells = vec![None; width * height];
+        for row in map.rows_on(z) {
+            for y in row.y1..=row.y2 {
+                for x in row.x1..=row.x2 {
+                    let index = (y - bounds.min.y) as usize * width + (x - bounds.min.x) as usize;
+                    cells[index] = Some(&row.tiles);
+                }
+            }
+        }
+        Some(Self { z, bounds, cells })
+    }
+
+    pub fn width(&self) -> usize {
+        (self.bounds.max.x - self.bounds.min.x) as usize + 1
+    }
+
+    pub fn height(&self) -> usize {
+        (self.bounds.max.y - self.bounds.min.y) as usize + 1
+    }
+
+    pub fn tiles_at(&self, point: Vec2) -> Option<&'a MapTileStackV1> {
+        if !self.bounds.contains(point) {
+            return None;
+        }
+        let index = (point.y - self.bounds.min.y) as usize * self.width() + (point.x - self.bounds.min.x) as usize;
+        self.cells[index]
+    }
+}
+
+fn top_tile(tiles: &MapTileStackV1) -> MapTileV1 {
+    tiles.layers.last().copied().unwrap_or(tiles.ground)
+}
+
+fn tile_char(tile: MapTileV1) -> char {
+    match tile {
+        MapTileV1::Water => '~',
+        MapTileV1::Grass => '.',
+        MapTileV1::Sand => ':',
+        MapTileV1::Dirt => ',',
+        MapTileV1::StoneFloor => '_',
+        MapTileV1::Road => '=',
+        MapTileV1::Swamp => '%',
+        MapTileV1::Snow => '*',
+        MapTileV1::GrassBorder | MapTileV1::SandBorder => '"',
+        MapTileV1::Tree => 'T',
+        MapTileV1::Bush => 'b',
+        MapTileV1::Stone => 'o',
+        MapTileV1::Wall => '#',
+        MapTileV1::StairUp => '<',
+        MapTileV1::Ladder => 'H',
+        MapTileV1::Hole => 'O',
+        MapTileV1::Door => '+',
+    }
+}
+
+fn tile_color(tile: MapTileV1) -> Rgb {
+    match tile {
+        MapTileV1::Water => [40, 90, 170],
+        MapTileV1::Grass => [70, 150, 60],
+        MapTileV1::Sand => [220, 200, 130],
+        MapTileV1::Dirt => [130, 95, 60],
+        MapTileV1::StoneFloor => [150, 150, 150],
+        MapTileV1::Road => [185, 170, 140],
+        MapTileV1::Swamp => [80, 100, 60],
+        MapTileV1::Snow => [235, 240, 245],
+        MapTileV1::GrassBorder => [90, 170, 80],
+        MapTileV1::SandBorder => [200, 185, 120],
+        MapTileV1::Tree => [25, 80, 30],
+        MapTileV1::Bush => [50, 120, 45],
+        MapTileV1::Stone => [110, 110, 110],
+        MapTileV1::Wall => [70, 60, 55],
+        MapTileV1::StairUp => [230, 170, 60],
+        MapTileV1::Ladder => [200, 140, 50],
+        MapTileV1::Hole => [20, 20, 20],
+        MapTileV1::Door => [150, 90, 40],
+    }
+}
+
+/// Marker drawn over a cell instead of its tile.
+fn marker_at(map: &MemoryMap, z: u8, point: Vec2) -> Option<(char, Rgb)> {
+    if map.spawn.z == z && Vec2::from(map.spawn) == point {
+        return Some(('@', SPAWN_COLOR));
+    }
+    map.temples
+        .iter()
+        .any(|temple| temple.position.z == z && Vec2::from(temple.position) == point)
+        .then_some(('A', TEMPLE_COLOR))
+}
+
+fn in_protection_zone(map: &MemoryMap, z: u8, point: Vec2) -> bool {
+    map.regions_on(z)
+        .any(|region| region.flags.protection_zone && Rect::from(region).contains(point))
+}
+
+fn region_label(flags: RegionFlagsV1) -> String {
+    [
+        (flags.protection_zone, "protection_zone"),
+        (flags.no_pvp, "no_pvp"),
+        (flags.no_logout, "no_logout"),
+        (flags.house, "house"),
+    ]
+    .into_iter()
+    .filter_map(|(set, name)| set.then_some(name))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// One character per tile, with a boundary line inserted before every sector edge.
+pub fn render_ascii(map: &MemoryMap, floor: &Floor) -> String {
+    let bounds = floor.bounds;
+    let boundary_row = |len: usize| "-".repeat(len);
+    let mut lines = Vec::with_capacity(floor.height());
+
+    for y in bounds.min.y..=bounds.max.y {
+        let mut line = String::with_capacity(floor.width() + floor.width() / SECTOR_SIZE as usize + 1);
+        for x in bounds.min.x..=bounds.max.x {
+            if x != bounds.min.x && x.is_multiple_of(SECTOR_SIZE) {
+                line.push('|');
+            }
+            let point = Vec2::new(x, y);
+            let cell = match marker_at(map, floor.z, point) {
+                Some((marker, _)) => marker,
+                None => floor.tiles_at(point).map_or(' ', |tiles| tile_char(top_tile(tiles))),
+            };
+            line.push(cell);
+        }
+        if y != bounds.min.y && y.is_multiple_of(SECTOR_SIZE) {
+            lines.push(boundary_row(line.chars().count()));
+        }
+        lines.push(line.trim_end().to_string());
+    }
+
+    let regions: String = map
+        .regions_on(floor.z)
+        .map(|region| {
+            format!(
+                "region x={}..={} y={}..={} {}\n",
+                region.x1,
+                region.x2,
+                region.y1,
+                region.y2,
+                region_label(region.flags)
+            )
+        })
+        .collect();
+
+    format!(
+        "floor z={} x={}..={} y={}..={}\n{}\n{}\n{}\n",
+        floor.z,
+        bounds.min.x,
+        bounds.max.x,
+        bounds.min.y,
+        bounds.max.y,
+        ASCII_LEGEND,
+        regions,
+        lines.join("\n")
+    )
+}
+
+/// `scale` pixels per tile; sector boundaries are darkened and markers fill their whole tile.
+pub fn render_png(map: &MemoryMap, floor: &Floor, scale: usize, path: &Path) -> Result<(), String> {
+    let width = floor.width() * scale;
+    let height = floor.height() * scale;
+    let mut pixels = vec![0u8; width * height * 3];
+
+    for y in floor.bounds.min.y..=floor.bounds.max.y {
+        for x in floor.bounds.min.x..=floor.bounds.max.x {
+            let point = Vec2::new(x, y);
+            let color = match marker_at(map, floor.z, point) {
+                Some((_, color)) => color,
+                None => floor.tiles_at(point).map_or([0, 0, 0], |tiles| tile_color(top_tile(tiles))),
+            };
+            let color = if in_protection_zone(map, floor.z, point) {
+                color.map(|c| c + (255 - c) / 3)
+            } else {
+                color
+            };
+            let on_boundary = x.is_multiple_of(SECTOR_SIZE) || y.is_multiple_of(SECTOR_SIZE);
+
+            let origin_x = (x - floor.bounds.min.x) as usize * scale;
+            let origin_y = (y - floor.bounds.min.y) as usize * scale;
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    let edge = (x.is_multiple_of(SECTOR_SIZE) && dx == 0) || (y.is_multiple_of(SECTOR_SIZE) && dy == 0);
+                    let color = if on_boundary && edge { color.map(|c| c / 3) } else { color };
+                    let offset = ((origin_y + dy) * width + origin_x + dx) * 3;
+                    pixels[offset..offset + 3].copy_from_slice(&color);
+                }
+            }
+        }
+    }
+
+    let file = File::create(path).map_err(|err| format!("Cannot create {}: {err}", path.display()))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|err| format!("Cannot write {}: {err}", path.display()))?;
+    writer
+        .write_image_data(&pixels)
+        .map_err(|err| format!("Cannot write {}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ikariadb_core::repository::world::types::{MapBlueprint, MapChunk, TempleSeed, Vec3};
+
+    #[test]
+    fn ascii_shows_top_tiles_and_markers() {
+        let blueprint = MapBlueprint {
+            chunks: vec![
+                MapChunk {
+                    rect: Rect::new(10, 10, 13, 11),
+                    z: 7,
+                    tiles: MapTileStackV1::new(MapTileV1::Grass),
+                },
+                MapChunk {
+                    rect: Rect::new(13, 11, 13, 11),
+                    z: 7,
+                    tiles: MapTileStackV1::new(MapTileV1::Grass).with_layer(MapTileV1::Tree),
+                },
+            ],
+            temples: vec![TempleSeed {
+                name: "Test".to_string(),
+                position: Vec3::new(12, 10, 7),
+            }],
+            ..MapBlueprint::default()
+        };
+        let map = MemoryMap::from_blueprint(&blueprint, Vec3::new(10, 10, 7));
+        let floor = Floor::new(&map, 7).unwrap();
+
+        let ascii = render_ascii(&map, &floor);
+
+        assert!(ascii.ends_with("@.A.\n...T\n"));
+    }
+}
@@ -0,0 +1,104 @@
+use ikariadb_core::repository::world::{
+    FloorLinkV1, MapRegionV1, MapV1,
+    types::{MapBlueprint, Rect, TempleSeed, Vec3},
+};
+use std::collections::BTreeSet;
+
+/// Stand-in for the module's map tables, filled with the same rows seeding inserts.
+pub struct MemoryMap {
+    pub rows: Vec<MapV1>,
+    pub links: Vec<FloorLinkV1>,
+    pub regions: Vec<MapRegionV1>,
+    pub temples: Vec<TempleSeed>,
+    pub spawn: Vec3,
+}
+
+impl MemoryMap {
+    pub fn from_blueprint(blueprint: &MapBlueprint, spawn: Vec3) -> Self {
+        Self {
+            rows: blueprint.map_rows(),
+            links: blueprint.floor_links(),
+            regions: blueprint.region_rows(),
+            temples: blueprint.temples.clone(),
+            spawn,
+        }
+    }
+
+    pub fn floors(&self) -> BTreeSet<u8> {
+        self.rows.iter().map(|row| row.z).collect()
+    }
+
+    pub fn rows_on(&self, z: u8) -> impl Iterator<Item = &MapV1> {
+        self.rows.iter().filter(move |row| row.z == z)
+    }
+
+    pub fn regions_on(&self, z: u8) -> impl Iterator<Item = &MapRegionV1> {
+        self.regions.iter().filter(move |region| region.z == z)
+    }
+
+    /// Bounding box of every row on floor `z`.
+    pub fn bounds(&self, z: u8) -> Option<Rect> {
+        let rows: Vec<_> = self.rows_on(z).collect();
+        Some(Rect::new(
+            rows.iter().map(|row| row.x1).min()?,
+            rows.iter().map(|row| row.y1).min()?,
+            rows.iter().map(|row| row.x2).max()?,
+            rows.iter().map(|row| row.y2).max()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ikariadb_core::{
+        constants::SECTOR_SIZE,
+        repository::world::types::{MapChunk, MapTileStackV1, MapTileV1, RegionFlagsV1, RegionSeed},
+    };
+
+    #[test]
+    fn rows_links_and_regions_are_seeded_like_the_module() {
+        let grass = MapTileStackV1::new(MapTileV1::Grass);
+        let blueprint = MapBlueprint {
+            chunks: vec![
+                MapChunk {
+                    rect: Rect::new(SECTOR_SIZE - 4, 0, SECTOR_SIZE - 1, 3),
+                    z: 7,
+                    tiles: grass.clone(),
+                },
+                MapChunk {
+                    rect: Rect::new(SECTOR_SIZE, 0, SECTOR_SIZE + 3, 3),
+                    z: 7,
+                    tiles: grass.clone(),
+                },
+                MapChunk {
+                    rect: Rect::new(SECTOR_SIZE - 4, 4, SECTOR_SIZE - 1, 4),
+                    z: 7,
+                    tiles: grass.clone(),
+                },
+                MapChunk {
+                    rect: Rect::new(SECTOR_SIZE + 3, 3, SECTOR_SIZE + 3, 3),
+                    z: 7,
+                    tiles: grass.with_layer(MapTileV1::Ladder),
+                },
+            ],
+            regions: vec![RegionSeed {
+                rect: Rect::new(SECTOR_SIZE - 2, 0, SECTOR_SIZE + 1, 1),
+                z: 7,
+                flags: RegionFlagsV1 {
+                    protection_zone: true,
+                    ..RegionFlagsV1::default()
+                },
+            }],
+            ..MapBlueprint::default()
+        };
+
+        let map = MemoryMap::from_blueprint(&blueprint, Vec3::new(0, 0, 7));
+
+        assert_eq!(map.rows.len(), 4);
+        assert_eq!(map.links.len(), 1);
+        assert_eq!(map.regions_on(7).count(), 2);
+        assert_eq!(map.bounds(7), Some(Rect::new(SECTOR_SIZE - 4, 0, SECTOR_SIZE + 3, 4)));
+        assert_eq!(map.bounds(8), None);
+    }
+}
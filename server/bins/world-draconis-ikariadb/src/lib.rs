@@ -5,8 +5,8 @@ use spacetimedb::{CaseConversionPolicy, ReducerContext, reducer};
 const CASE_CONVERSION_POLICY: CaseConversionPolicy = CaseConversionPolicy::None;
 
 #[reducer(init)]
-pub fn init(ctx: &ReducerContext) {
-    ikariadb_core::init(ctx);
+pub fn init(ctx: &ReducerContext) -> ServiceResult<()> {
+    ikariadb_core::init(ctx, ikariadb_core::worlds::draconis())
 }
 
 #[reducer(client_connected)]
@@ -0,0 +1,111 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use ikaria_shared::constants::GROUND_LEVEL;
+use ikariadb_core::{
+    constants::{MAP_VIEW_RADIUS, SECTOR_SIZE},
+    repository::world::{
+        OccupiedTileV1,
+        sector_grid::SectorGrid,
+        types::{Rect, Vec2, Vec3},
+    },
+};
+use std::{collections::BTreeMap, hint::black_box};
+
+const WORLD_SECTORS: u16 = 8;
+const OCCUPIED_TILES: usize = 20_000;
+
+/// Occupied tiles scattered over a few floors, grouped by sector the way the btree index on `sector_key` is.
+fn occupied_index() -> BTreeMap<u64, Vec<OccupiedTileV1>> {
+    let mut index: BTreeMap<u64, Vec<OccupiedTileV1>> = BTreeMap::new();
+    let side = WORLD_SECTORS as u64 * SECTOR_SIZE as u64;
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    for character_id in 0..OCCUPIED_TILES as u64 {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        let pos = Vec3::new(
+            ((state >> 16) % side) as u16,
+            ((state >> 32) % side) as u16,
+            GROUND_LEVEL - (state >> 60) as u8 % 3,
+        );
+        index.entry(pos.sector_key()).or_default().push(OccupiedTileV1 {
+            map_id: pos.map_id(),
+            sector_key: pos.sector_key(),
+            character_ids: vec![character_id],
+        });
+    }
+    index
+}
+
+fn lookup(index: &BTreeMap<u64, Vec<OccupiedTileV1>>, sector_key: u64) -> impl Iterator<Item = &OccupiedTileV1> {
+    index.get(&sector_key).into_iter().flatten()
+}
+
+/// The loop views used before `SectorGrid`, kept as the baseline.
+fn hand_rolled(index: &BTreeMap<u64, Vec<OccupiedTileV1>>, rect: Rect, floors: [u8; 2]) -> usize {
+    let mut found = 0;
+    for z in floors {
+        for sx in rect.min.x / SECTOR_SIZE..=rect.max.x / SECTOR_SIZE {
+            for sy in rect.min.y / SECTOR_SIZE..=rect.max.y / SECTOR_SIZE {
+                let sector_key = ((z as u64) << 32) | ((sx as u64) << 16) | (sy as u64);
+                for tile in lookup(index, sector_key) {
+                    let pos = Vec3::from_map_id(tile.map_id);
+                    if pos.z == z && rect.contains(pos.into()) {
+                        found += 1;
+                    }
+                }
+            }
+        }
+    }
+    found
+}
+
+fn bench_sector_grid(c: &mut Criterion) {
+    let index = occupied_index();
+    // Sits on a sector corner so the view spans four sectors per floor, the worst case for a view radius.
+    let center = Vec2::new(3 * SECTOR_SIZE, 3 * SECTOR_SIZE);
+    let floors = GROUND_LEVEL - 1..=GROUND_LEVEL;
+    let rect = Rect::around(center, MAP_VIEW_RADIUS);
+
+    let mut group = c.benchmark_group("occupied_tiles_in_view");
+    group.bench_function("full_scan", |b| {
+        b.iter(|| {
+            index
+                .values()
+                .flatten()
+                .filter(|tile| {
+                    let pos = Vec3::from_map_id(tile.map_id);
+                    floors.contains(&pos.z) && rect.contains(pos.into())
+                })
+                .count()
+        })
+    });
+    group.bench_function("hand_rolled", |b| {
+        b.iter(|| hand_rolled(&index, black_box(rect), [GROUND_LEVEL - 1, GROUND_LEVEL]))
+    });
+    group.bench_function("sector_grid_radius", |b| {
+        b.iter(|| {
+            SectorGrid::radius(black_box(center), MAP_VIEW_RADIUS, floors.clone())
+                .query(|sector_key| lookup(&index, sector_key))
+                .count()
+        })
+    });
+    group.bench_function("sector_grid_ring", |b| {
+        b.iter(|| {
+            SectorGrid::ring(black_box(center), MAP_VIEW_RADIUS - 1, MAP_VIEW_RADIUS, floors.clone())
+                .query(|sector_key| lookup(&index, sector_key))
+                .count()
+        })
+    });
+    group.finish();
+
+    c.bench_function("sector_keys_wide_ring", |b| {
+        b.iter(|| {
+            SectorGrid::ring(black_box(Vec2::new(1024, 1024)), 900, 1000, floors.clone())
+                .sector_keys()
+                .count()
+        })
+    });
+}
+
+criterion_group!(benches, bench_sector_grid);
+criterion_main!(benches);
@@ -0,0 +1,96 @@
+use crate::{
+    constants::{
+        DEFAULT_CHARACTER_DELETION_GRACE_DAYS, DEFAULT_CHARACTER_NAME_RESERVATION_DAYS, DEFAULT_CHARACTER_RENAME_COOLDOWN_DAYS,
+        DEFAULT_SPAWN_X, DEFAULT_SPAWN_Y, INITIAL_MAP,
+    },
+    error::ServiceResult,
+    repository::{
+        character::types::StartingStatsV1,
+        world::{
+            WorldConfigV1,
+            terrain::{TerrainSettings, generate_terrain},
+            tiled::parse_tiled_map,
+            types::{CornerRuleV1, MapBlueprint, PvpModeV1, Vec3, WorldFeaturesV1},
+        },
+    },
+};
+use ikaria_shared::constants::GROUND_LEVEL;
+
+pub const WORLD_CONFIG_ID: u64 = 1;
+
+/// Where a world takes its map from when it is first published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapSource {
+    /// Tiled JSON export embedded in the world binary.
+    Tiled(&'static str),
+    /// Seeded island generator; the generated spawn replaces `WorldConfig::spawn`.
+    Procedural(TerrainSettings),
+}
+
+impl MapSource {
+    /// Map this source seeds, with the spawn the generator picked if it picked one.
+    pub fn blueprint(&self) -> ServiceResult<(MapBlueprint, Option<Vec3>)> {
+        match self {
+            MapSource::Tiled(tiled_json) => Ok((parse_tiled_map(tiled_json)?, None)),
+            MapSource::Procedural(settings) => {
+                let terrain = generate_terrain(settings)?;
+                Ok((terrain.blueprint, Some(terrain.spawn)))
+            },
+        }
+    }
+}
+
+/// Per-world settings passed by each world binary into `ikariadb_core::init`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldConfig {
+    pub map_source: MapSource,
+    pub spawn: Vec3,
+    pub pvp_mode: PvpModeV1,
+    pub features: WorldFeaturesV1,
+    pub corner_rule: CornerRuleV1,
+    /// `None` means unlimited character slots.
+    pub max_characters_per_user: Option<u16>,
+    /// Days between a deletion request and the purge of the character.
+    pub character_deletion_grace_days: u16,
+    /// Days a character has to wait between two renames.
+    pub character_rename_cooldown_days: u16,
+    /// Days a name given up by a rename stays reserved for the character that used it.
+    pub character_name_reservation_days: u16,
+    pub starting_stats: StartingStatsV1,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self {
+            map_source: MapSource::Tiled(INITIAL_MAP),
+            spawn: Vec3::new(DEFAULT_SPAWN_X, DEFAULT_SPAWN_Y, GROUND_LEVEL),
+            pvp_mode: PvpModeV1::Optional,
+            features: WorldFeaturesV1::default(),
+            corner_rule: CornerRuleV1::default(),
+            max_characters_per_user: None,
+            character_deletion_grace_days: DEFAULT_CHARACTER_DELETION_GRACE_DAYS,
+            character_rename_cooldown_days: DEFAULT_CHARACTER_RENAME_COOLDOWN_DAYS,
+            character_name_reservation_days: DEFAULT_CHARACTER_NAME_RESERVATION_DAYS,
+            starting_stats: StartingStatsV1::default(),
+        }
+    }
+}
+
+impl From<&WorldConfig> for WorldConfigV1 {
+    fn from(config: &WorldConfig) -> Self {
+        Self {
+            world_config_id: WORLD_CONFIG_ID,
+            spawn_x: config.spawn.x,
+            spawn_y: config.spawn.y,
+            spawn_z: config.spawn.z,
+            pvp_mode: config.pvp_mode,
+            features: config.features,
+            corner_rule: config.corner_rule,
+            max_characters_per_user: config.max_characters_per_user,
+            character_deletion_grace_days: config.character_deletion_grace_days,
+            character_rename_cooldown_days: config.character_rename_cooldown_days,
+            character_name_reservation_days: config.character_name_reservation_days,
+            starting_stats: config.starting_stats,
+        }
+    }
+}
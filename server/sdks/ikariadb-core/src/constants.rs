@@ -11,17 +11,59 @@ pub const DEFAULT_CHARACTER_CAPACITY: u32 = 15 * KG_TO_G;
 pub const DEFAULT_CHARACTER_SPEED: u16 = 120;
 pub const DEFAULT_CHARACTER_ATTACK_SPEED: u16 = 100;
 
+/// Days a character stays recoverable after its deletion is requested.
+pub const DEFAULT_CHARACTER_DELETION_GRACE_DAYS: u16 = 7;
+pub const DEFAULT_CHARACTER_RENAME_COOLDOWN_DAYS: u16 = 30;
+/// Days a name given up by a rename stays unavailable to every other character.
+pub const DEFAULT_CHARACTER_NAME_RESERVATION_DAYS: u16 = 90;
+
 pub const DEFAULT_SPAWN_X: u16 = 1152;
 pub const DEFAULT_SPAWN_Y: u16 = 1152;
 
 pub const MAP_VIEW_RADIUS: u16 = 32;
+/// Side of the square tile blocks the minimap tracks as explored; must divide `SECTOR_SIZE`.
+pub const MINIMAP_BLOCK_SIZE: u16 = 8;
+/// How far around a character the minimap is revealed.
+pub const MINIMAP_EXPLORE_RADIUS: u16 = 8;
+/// How far from its temple a character may respawn when the temple tile itself is taken.
+pub const TEMPLE_SPAWN_RADIUS: u16 = 3;
+/// How far from a portal destination a character may land when the destination itself is taken.
+pub const PORTAL_ARRIVAL_RADIUS: u16 = 2;
+
+/// Tiled JSON export seeded into a fresh world.
+pub const INITIAL_MAP: &str = include_str!("../maps/initial.json");
 
 pub const SECTOR_SIZE: u16 = 256;
 
 pub const MOVEMENT_COOLDOWN_FACTOR: u64 = 40_000;
+/// Step time percentage of plain ground; tiles scale the movement cooldown relative to it.
+pub const BASE_STEP_TIME_PERCENT: u16 = 100;
 pub const MOVEMENT_INTENTION_WINDOW_MS: u64 = 25;
+pub const PUSH_COOLDOWN_MS: u64 = 2000;
+pub const TURN_COOLDOWN_MS: u64 = 150;
+/// Walkable chunks remembered per character so stepping back and forth across chunk borders skips the map lookup.
+pub const WALKED_MAP_CHUNK_CACHE_SIZE: usize = 4;
+pub const WALK_CACHE_STATS_ID: u64 = 1;
+
+/// Upper bound on tiles expanded by a single path search.
+pub const PATHFINDING_NODE_BUDGET: usize = 4096;
+pub const WALK_MAX_REPLANS: u8 = 3;
 
 pub const CHAT_MESSAGE_MIN_LEN: usize = 1;
 pub const CHAT_MESSAGE_MAX_LEN: usize = 1024;
 pub const CHAT_BUBBLE_BASE_DURATION_MS: u64 = 3000;
 pub const CHAT_BUBBLE_MS_PER_CHAR: u64 = 100;
+
+pub const HOUSE_NAME_MIN_LEN: usize = 3;
+pub const HOUSE_NAME_MAX_LEN: usize = 48;
+pub const HOUSE_RENT_PERIOD_DAYS: u64 = 30;
+/// How far from a house door characters without access are put when they are moved out.
+pub const HOUSE_EXIT_RADIUS: u16 = 2;
+
+pub const WORLD_CLOCK_ID: u64 = 1;
+/// In-game minutes that pass per real minute; 24 makes a full day last one real hour.
+pub const WORLD_CLOCK_SPEED: u32 = 24;
+pub const WORLD_CLOCK_TICK_SECS: u64 = 10;
+/// Hour a fresh world's clock starts at.
+pub const WORLD_CLOCK_START_HOUR: u16 = 8;
+pub const WEATHER_TICK_SECS: u64 = 300;
@@ -1,60 +1,58 @@
 use crate::{
-    constants::{MAP_VIEW_RADIUS, SECTOR_SIZE},
+    constants::MAP_VIEW_RADIUS,
     repository::{
         character::online_character_v1__view,
+        environment::{sector_weather_v1__view, types::weather_sector_key},
         world::{
-            OccupiedTileV1, occupied_tile_v1__view, online_character_position_v1__view,
+            MapV1, OccupiedTileV1, map_v1__view, occupied_tile_v1__view, online_character_position_v1__view,
+            sector_grid::SectorGrid,
             types::{Rect, Vec3},
+            visibility::VisibilityGrid,
         },
     },
 };
 use spacetimedb::ViewContext;
 
-pub type ZRange = (u8, u8);
-
-pub fn find_ranges(ctx: &ViewContext) -> Option<(Rect, ZRange)> {
+pub fn find_viewer(ctx: &ViewContext) -> Option<Vec3> {
     let current = ctx.db.online_character_v1().user_id().find(ctx.sender())?;
     let position = ctx
         .db
         .online_character_position_v1()
         .character_id()
         .find(current.character_id)?;
+    Some(Vec3::new(position.x, position.y, position.z))
+}
 
-    let rect = Rect::new(
-        position.x.saturating_sub(MAP_VIEW_RADIUS),
-        position.y.saturating_sub(MAP_VIEW_RADIUS),
-        position.x.saturating_add(MAP_VIEW_RADIUS),
-        position.y.saturating_add(MAP_VIEW_RADIUS),
-    );
-    let min_z = position.z.saturating_sub(1);
-    let max_z = position.z.saturating_add(1);
+/// Visibility of the sender's character, built from the map chunks of its own floor, which are returned alongside.
+/// The weather over the character narrows how far away others can be seen.
+pub fn find_visibility(ctx: &ViewContext) -> Option<(VisibilityGrid, Vec<MapV1>)> {
+    let viewer = find_viewer(ctx)?;
+    let weather = weather_sector_key(viewer)
+        .and_then(|sector_key| ctx.db.sector_weather_v1().sector_key().find(sector_key))
+        .map(|sector| sector.weather)
+        .unwrap_or_default();
+    let sight_radius = (MAP_VIEW_RADIUS as u32 * weather.sight_percent() as u32 / 100) as u16;
+    let mut visibility = VisibilityGrid::new(viewer, MAP_VIEW_RADIUS).with_sight_radius(sight_radius);
+    let chunks = find_map_chunks_in(ctx, visibility.rect(), viewer.z);
+    for chunk in &chunks {
+        visibility.add_tiles(Rect::from(chunk), chunk.z, &chunk.tiles);
+    }
+    Some((visibility, chunks))
+}
 
-    Some((rect, (min_z, max_z)))
+pub fn find_map_chunks_in(ctx: &ViewContext, rect: Rect, z: u8) -> Vec<MapV1> {
+    SectorGrid::rect(rect, z..=z)
+        .query(|sector_key| ctx.db.map_v1().sector_key().filter(sector_key))
+        .collect()
 }
 
 pub fn iter_nearby_occupied(ctx: &ViewContext) -> Vec<OccupiedTileV1> {
-    let Some((rect, (min_z, max_z))) = find_ranges(ctx) else {
+    let Some((visibility, _)) = find_visibility(ctx) else {
         return Vec::new();
     };
 
-    let sec_min_x = rect.min.x / SECTOR_SIZE;
-    let sec_max_x = rect.max.x / SECTOR_SIZE;
-    let sec_min_y = rect.min.y / SECTOR_SIZE;
-    let sec_max_y = rect.max.y / SECTOR_SIZE;
-
-    let mut occupied = Vec::new();
-    for z in min_z..=max_z {
-        for sx in sec_min_x..=sec_max_x {
-            for sy in sec_min_y..=sec_max_y {
-                let sector_key = ((z as u64) << 32) | ((sx as u64) << 16) | (sy as u64);
-                for tile in ctx.db.occupied_tile_v1().sector_key().filter(sector_key) {
-                    let pos = Vec3::from_map_id(tile.map_id);
-                    if pos.z == z && rect.contains(pos.into()) {
-                        occupied.push(tile);
-                    }
-                }
-            }
-        }
-    }
-    occupied
+    SectorGrid::rect(visibility.rect(), visibility.floors())
+        .query(|sector_key| ctx.db.occupied_tile_v1().sector_key().filter(sector_key))
+        .filter(|tile| visibility.can_see(Vec3::from_map_id(tile.map_id)))
+        .collect()
 }
@@ -1,6 +1,9 @@
 use crate::{
     error::{ErrorMapper, ServiceError, ServiceResult},
-    repository::character::{CharacterV1, services::CharacterReducerContext},
+    repository::{
+        character::{CharacterV1, services::CharacterReducerContext},
+        user::services::UserReducerContext,
+    },
 };
 use spacetimedb::ReducerContext;
 use thiserror::Error;
@@ -28,6 +31,8 @@ pub trait ReducerContextRequirements {
 
     fn require_internal_access(&self) -> ServiceResult<()>;
 
+    fn require_game_master(&self) -> ServiceResult<()>;
+
     fn require_online(&self) -> ServiceResult<CharacterV1>;
 }
 
@@ -39,6 +44,13 @@ impl ReducerContextRequirements for ReducerContext {
         Ok(())
     }
 
+    fn require_game_master(&self) -> ServiceResult<()> {
+        if !self.user_services().is_game_master(self.sender()) {
+            return Err(ServiceError::unauthorized(self.sender(), "Game master access required"));
+        }
+        Ok(())
+    }
+
     fn require_online(&self) -> ServiceResult<CharacterV1> {
         self.character_services().get_current(self.sender())
     }
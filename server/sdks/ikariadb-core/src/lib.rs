@@ -1,14 +1,17 @@
+pub mod config;
 pub mod constants;
 pub mod error;
 pub mod extend;
 pub mod repository;
+pub mod worlds;
 
 use spacetimedb::ReducerContext;
 
+pub use config::{MapSource, WorldConfig};
 pub use error::ServiceResult;
 
-pub fn init(ctx: &ReducerContext) {
-    repository::init(ctx);
+pub fn init(ctx: &ReducerContext, config: WorldConfig) -> ServiceResult<()> {
+    repository::init(ctx, config)
 }
 
 pub fn identity_connected(ctx: &ReducerContext) -> ServiceResult<()> {
@@ -1,6 +1,9 @@
-use self::types::{ClassV1, RaceV1};
-use crate::repository::character::types::GenderV1;
-use spacetimedb::{Identity, Timestamp, table};
+use self::{
+    services::CharacterReducerContext,
+    types::{ClassV1, RaceV1},
+};
+use crate::{error::ServiceResult, extend::validate::ReducerContextRequirements, repository::character::types::GenderV1};
+use spacetimedb::{Identity, ReducerContext, ScheduleAt, Timestamp, reducer, table};
 
 pub mod reducers;
 pub mod services;
@@ -45,3 +48,37 @@ pub struct OnlineCharacterV1 {
     pub character_id: u64,
     pub signed_in_at: Timestamp,
 }
+
+/// Names a character gave up by renaming, newest last; `name` is canonical like `CharacterV1::name`.
+#[table(accessor = character_name_history_v1, private)]
+pub struct CharacterNameHistoryV1 {
+    #[auto_inc]
+    #[primary_key]
+    pub character_name_history_id: u64,
+    #[index(btree)]
+    pub character_id: u64,
+    #[index(btree)]
+    pub name: String,
+    pub display_name: String,
+    pub renamed_at: Timestamp,
+    pub reserved_until: Timestamp,
+}
+
+/// Character waiting to be purged; cancelling the deletion removes the row before it fires.
+#[table(accessor = character_deletion_v1, private, scheduled(character_deletion_scheduled_v1))]
+pub struct CharacterDeletionV1 {
+    #[primary_key]
+    pub character_id: u64,
+    #[index(btree)]
+    pub user_id: Identity,
+    pub requested_at: Timestamp,
+    pub purge_at: Timestamp,
+    pub scheduled_at: ScheduleAt,
+}
+
+#[reducer]
+pub fn character_deletion_scheduled_v1(ctx: &ReducerContext, timer: CharacterDeletionV1) -> ServiceResult<()> {
+    ctx.require_internal_access()?;
+    ctx.character_services().purge_character(timer.character_id)?;
+    Ok(())
+}
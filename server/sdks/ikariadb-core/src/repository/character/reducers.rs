@@ -25,3 +25,22 @@ pub fn unselect_character_v1(ctx: &ReducerContext) -> ServiceResult<()> {
     ctx.character_services().unselect_character(ctx.sender())?;
     Ok(())
 }
+
+#[reducer]
+pub fn rename_character_v1(ctx: &ReducerContext, character_id: u64, display_name: String) -> ServiceResult<()> {
+    ctx.character_services()
+        .rename_character(ctx.sender(), character_id, display_name)?;
+    Ok(())
+}
+
+#[reducer]
+pub fn request_delete_character_v1(ctx: &ReducerContext, character_id: u64) -> ServiceResult<()> {
+    ctx.character_services().request_deletion(ctx.sender(), character_id)?;
+    Ok(())
+}
+
+#[reducer]
+pub fn cancel_delete_character_v1(ctx: &ReducerContext, character_id: u64) -> ServiceResult<()> {
+    ctx.character_services().cancel_deletion(ctx.sender(), character_id)?;
+    Ok(())
+}
@@ -1,21 +1,20 @@
 use crate::{
-    constants::{
-        CHARACTER_NAME_MAX_LEN, CHARACTER_NAME_MIN_LEN, DEFAULT_CHARACTER_ATTACK_SPEED, DEFAULT_CHARACTER_CAPACITY,
-        DEFAULT_CHARACTER_EXPERIENCE, DEFAULT_CHARACTER_HEALTH, DEFAULT_CHARACTER_LEVEL, DEFAULT_CHARACTER_MANA,
-        DEFAULT_CHARACTER_SPEED,
-    },
+    constants::{CHARACTER_NAME_MAX_LEN, CHARACTER_NAME_MIN_LEN},
     error::{ErrorMapper, ResultExt, ServiceError, ServiceResult},
-    extend::validate::ReducerContextRequirements,
+    extend::{duration::DurationExt, validate::ReducerContextRequirements},
     repository::{
         character::{
-            CharacterStatsV1, CharacterV1, OnlineCharacterV1, character_stats_v1, character_v1, online_character_v1,
-            types::{ClassV1, GenderV1, RaceV1},
+            CharacterDeletionV1, CharacterNameHistoryV1, CharacterStatsV1, CharacterV1, OnlineCharacterV1,
+            character_deletion_v1, character_name_history_v1, character_stats_v1, character_v1, online_character_v1,
+            types::{ClassV1, GenderV1, RaceV1, StartingStatsV1},
         },
         event::services::EventReducerContext,
+        world::services::WorldReducerContext,
     },
 };
-use spacetimedb::{Identity, ReducerContext, Table};
-use std::ops::Deref;
+use log::{info, warn};
+use spacetimedb::{Identity, ReducerContext, Table, Timestamp};
+use std::{ops::Deref, time::Duration};
 use thiserror::Error;
 
 pub trait CharacterReducerContext {
@@ -97,6 +96,16 @@ impl CharacterServices<'_> {
         race: RaceV1,
     ) -> ServiceResult<()> {
         let (display_name, canonical_name) = self.prepare_character_names(display_name)?;
+        if self.is_name_reserved(&canonical_name, None) {
+            return Err(CharacterError::name_taken(display_name));
+        }
+
+        let config = self.world_services().config();
+        if let Some(max_characters) = config.max_characters_per_user
+            && self.db.character_v1().user_id().filter(user_id).count() >= max_characters as usize
+        {
+            return Err(CharacterError::character_limit_reached(max_characters));
+        }
 
         let character = self.db.character_v1().try_insert(CharacterV1 {
             character_id: 0,
@@ -118,7 +127,7 @@ impl CharacterServices<'_> {
 
         self.db
             .character_stats_v1()
-            .try_insert(CharacterStatsV1::new(&character))
+            .try_insert(CharacterStatsV1::new(&character, &config.starting_stats))
             .map_conflict()?;
 
         self.publish().character_created(user_id, character.character_id)?;
@@ -132,6 +141,14 @@ impl CharacterServices<'_> {
         if character.user_id != user_id {
             return Err(CharacterError::character_ownership_mismatch(character_id, user_id));
         }
+        if self.is_pending_deletion(character_id) {
+            return Err(CharacterError::deletion_pending(character_id));
+        }
+        if let Ok(current) = self.get_current(user_id)
+            && current.character_id != character_id
+        {
+            self.world_services().ensure_can_log_out(current.character_id)?;
+        }
 
         self.db.online_character_v1().user_id().insert_or_update(OnlineCharacterV1 {
             user_id,
@@ -144,7 +161,8 @@ impl CharacterServices<'_> {
     }
 
     pub fn unselect_character(&self, user_id: Identity) -> ServiceResult<()> {
-        self.get_current(user_id)?;
+        let current = self.get_current(user_id)?;
+        self.world_services().ensure_can_log_out(current.character_id)?;
         self.publish().character_unselected(user_id)?;
         Ok(())
     }
@@ -153,6 +171,163 @@ impl CharacterServices<'_> {
         self.db.online_character_v1().user_id().delete(user_id);
     }
 
+    /// Renames one of the user's characters with the same rules as creation. The old name is kept in the history and
+    /// stays reserved for this character, so it can take it back but nobody else can pick it up in the meantime.
+    pub fn rename_character(&self, user_id: Identity, character_id: u64, display_name: String) -> ServiceResult<()> {
+        let mut character = self.get_offline(character_id)?;
+        if character.user_id != user_id {
+            return Err(CharacterError::character_ownership_mismatch(character_id, user_id));
+        }
+        if self.is_pending_deletion(character_id) {
+            return Err(CharacterError::deletion_pending(character_id));
+        }
+
+        let (display_name, canonical_name) = self.prepare_character_names(display_name)?;
+        if display_name == character.display_name {
+            return Err(CharacterError::name_unchanged(display_name));
+        }
+
+        let config = self.world_services().config();
+        if let Some(last_rename) = self.find_last_rename(character_id) {
+            let can_rename_at = last_rename.renamed_at + Duration::from_days_ext(config.character_rename_cooldown_days as u64);
+            if self.timestamp < can_rename_at {
+                return Err(CharacterError::rename_on_cooldown(character_id, can_rename_at));
+            }
+        }
+
+        let taken_by_other = self
+            .db
+            .character_v1()
+            .name()
+            .find(&canonical_name)
+            .is_some_and(|other| other.character_id != character_id);
+        if taken_by_other || self.is_name_reserved(&canonical_name, Some(character_id)) {
+            return Err(CharacterError::name_taken(display_name));
+        }
+
+        self.db.character_name_history_v1().insert(CharacterNameHistoryV1 {
+            character_name_history_id: 0,
+            character_id,
+            name: character.name,
+            display_name: character.display_name,
+            renamed_at: self.timestamp,
+            reserved_until: self.timestamp + Duration::from_days_ext(config.character_name_reservation_days as u64),
+        });
+
+        character.name = canonical_name;
+        character.display_name = display_name;
+        self.db.character_v1().character_id().update(character);
+        Ok(())
+    }
+
+    pub fn find_last_rename(&self, character_id: u64) -> Option<CharacterNameHistoryV1> {
+        self.db
+            .character_name_history_v1()
+            .character_id()
+            .filter(character_id)
+            .max_by_key(|entry| entry.renamed_at)
+    }
+
+    /// Whether a canonical name was recently given up by a character other than `character_id`.
+    /// Expired entries of the name are dropped on the way, so the history only keeps what still matters.
+    pub fn is_name_reserved(&self, name: &str, character_id: Option<u64>) -> bool {
+        let rename_cooldown = Duration::from_days_ext(self.world_services().config().character_rename_cooldown_days as u64);
+        let entries: Vec<CharacterNameHistoryV1> = self.db.character_name_history_v1().name().filter(name).collect();
+
+        let mut reserved = false;
+        for entry in entries {
+            if entry.is_expired(self.timestamp, rename_cooldown) {
+                self.db
+                    .character_name_history_v1()
+                    .character_name_history_id()
+                    .delete(entry.character_name_history_id);
+            } else {
+                reserved |= entry.reserves(character_id, self.timestamp);
+            }
+        }
+        reserved
+    }
+
+    /// Drops the character's expired name history; names still on hold stay reserved after it is gone.
+    fn prune_name_history(&self, character_id: u64) {
+        let expired: Vec<u64> = self
+            .db
+            .character_name_history_v1()
+            .character_id()
+            .filter(character_id)
+            .filter(|entry| entry.is_expired(self.timestamp, Duration::ZERO))
+            .map(|entry| entry.character_name_history_id)
+            .collect();
+        for character_name_history_id in expired {
+            self.db
+                .character_name_history_v1()
+                .character_name_history_id()
+                .delete(character_name_history_id);
+        }
+    }
+
+    pub fn is_pending_deletion(&self, character_id: u64) -> bool {
+        self.db.character_deletion_v1().character_id().find(character_id).is_some()
+    }
+
+    /// Schedules the purge of one of the user's characters after the world's grace period; the selected character
+    /// cannot be deleted and a pending one cannot be selected.
+    pub fn request_deletion(&self, user_id: Identity, character_id: u64) -> ServiceResult<()> {
+        let character = self.get_offline(character_id)?;
+        if character.user_id != user_id {
+            return Err(CharacterError::character_ownership_mismatch(character_id, user_id));
+        }
+        if self.find_online(character_id).is_some() {
+            return Err(CharacterError::character_selected(character_id));
+        }
+        if self.is_pending_deletion(character_id) {
+            return Err(CharacterError::deletion_pending(character_id));
+        }
+
+        let grace_days = self.world_services().config().character_deletion_grace_days;
+        let purge_at = self.timestamp + Duration::from_days_ext(grace_days as u64);
+        self.db.character_deletion_v1().insert(CharacterDeletionV1 {
+            character_id,
+            user_id,
+            requested_at: self.timestamp,
+            purge_at,
+            scheduled_at: purge_at.into(),
+        });
+        Ok(())
+    }
+
+    pub fn cancel_deletion(&self, user_id: Identity, character_id: u64) -> ServiceResult<()> {
+        let character = self.get_offline(character_id)?;
+        if character.user_id != user_id {
+            return Err(CharacterError::character_ownership_mismatch(character_id, user_id));
+        }
+        if !self.db.character_deletion_v1().character_id().delete(character_id) {
+            return Err(CharacterError::deletion_not_pending(character_id));
+        }
+        Ok(())
+    }
+
+    /// Removes the character and everything hanging off it, which frees its name for new characters.
+    pub fn purge_character(&self, character_id: u64) -> ServiceResult<()> {
+        let Some(character) = self.find_offline(character_id) else {
+            return Ok(());
+        };
+        // Pending characters cannot be selected, but the schedule row is already consumed, so a selected one is
+        // signed out rather than left behind.
+        if self.find_online(character_id).is_some() {
+            warn!("Character {character_id} is selected, signing it out before the purge");
+            self.publish().character_unselected(character.user_id)?;
+        }
+
+        self.publish().character_deleted(character.user_id, character_id)?;
+        self.prune_name_history(character_id);
+        self.db.character_deletion_v1().character_id().delete(character_id);
+        self.db.character_stats_v1().character_id().delete(character_id);
+        self.db.character_v1().character_id().delete(character_id);
+        info!("Character {character_id} '{}' purged", character.name);
+        Ok(())
+    }
+
     fn prepare_character_names(&self, display_name: String) -> ServiceResult<(String, String)> {
         self.validate_str(
             &display_name,
@@ -204,18 +379,30 @@ impl CharacterServices<'_> {
     }
 }
 
+impl CharacterNameHistoryV1 {
+    /// Names stay reserved for everyone but the character that gave them up until the reservation runs out.
+    pub fn reserves(&self, character_id: Option<u64>, now: Timestamp) -> bool {
+        character_id != Some(self.character_id) && now < self.reserved_until
+    }
+
+    /// Entries can go once they neither hold their name nor hold back the character's next rename.
+    pub fn is_expired(&self, now: Timestamp, rename_cooldown: Duration) -> bool {
+        now >= self.reserved_until && now >= self.renamed_at + rename_cooldown
+    }
+}
+
 impl CharacterStatsV1 {
-    pub fn new(character: &CharacterV1) -> Self {
+    pub fn new(character: &CharacterV1, starting: &StartingStatsV1) -> Self {
         Self {
             character_id: character.character_id,
             user_id: character.user_id,
-            level: DEFAULT_CHARACTER_LEVEL,
-            experience: DEFAULT_CHARACTER_EXPERIENCE,
-            health: DEFAULT_CHARACTER_HEALTH,
-            mana: DEFAULT_CHARACTER_MANA,
-            capacity: DEFAULT_CHARACTER_CAPACITY,
-            speed: DEFAULT_CHARACTER_SPEED,
-            attack_speed: DEFAULT_CHARACTER_ATTACK_SPEED,
+            level: starting.level,
+            experience: starting.experience,
+            health: starting.health,
+            mana: starting.mana,
+            capacity: starting.capacity,
+            speed: starting.speed,
+            attack_speed: starting.attack_speed,
         }
     }
 }
@@ -231,6 +418,9 @@ enum CharacterError {
     #[error("Character {character_id} does not belong to user {user_id}")]
     CharacterOwnershipMismatch { character_id: u64, user_id: Identity },
 
+    #[error("Character limit of {0} per user reached")]
+    CharacterLimitReached(u16),
+
     #[error("Character name '{0}' is already taken")]
     NameTaken(String),
 
@@ -242,6 +432,21 @@ enum CharacterError {
 
     #[error("Character name must contain at least one letter")]
     NameWithoutLetters,
+
+    #[error("Character {0} is currently selected")]
+    CharacterSelected(u64),
+
+    #[error("Character {0} is scheduled for deletion")]
+    DeletionPending(u64),
+
+    #[error("Character {0} is not scheduled for deletion")]
+    DeletionNotPending(u64),
+
+    #[error("Character is already named '{0}'")]
+    NameUnchanged(String),
+
+    #[error("Character {character_id} cannot be renamed again until {can_rename_at}")]
+    RenameOnCooldown { character_id: u64, can_rename_at: Timestamp },
 }
 
 impl CharacterError {
@@ -257,6 +462,10 @@ impl CharacterError {
         Self::CharacterOwnershipMismatch { character_id, user_id }.map_forbidden_error()
     }
 
+    fn character_limit_reached(max_characters: u16) -> ServiceError {
+        Self::CharacterLimitReached(max_characters).map_forbidden_error()
+    }
+
     fn name_taken(display_name: String) -> ServiceError {
         Self::NameTaken(display_name).map_conflict_error()
     }
@@ -272,6 +481,30 @@ impl CharacterError {
     fn name_without_letters() -> ServiceError {
         Self::NameWithoutLetters.map_validation_error()
     }
+
+    fn character_selected(character_id: u64) -> ServiceError {
+        Self::CharacterSelected(character_id).map_conflict_error()
+    }
+
+    fn deletion_pending(character_id: u64) -> ServiceError {
+        Self::DeletionPending(character_id).map_conflict_error()
+    }
+
+    fn deletion_not_pending(character_id: u64) -> ServiceError {
+        Self::DeletionNotPending(character_id).map_not_found_error()
+    }
+
+    fn name_unchanged(display_name: String) -> ServiceError {
+        Self::NameUnchanged(display_name).map_validation_error()
+    }
+
+    fn rename_on_cooldown(character_id: u64, can_rename_at: Timestamp) -> ServiceError {
+        Self::RenameOnCooldown {
+            character_id,
+            can_rename_at,
+        }
+        .map_validation_error()
+    }
 }
 
 #[cfg(test)]
@@ -377,4 +610,42 @@ mod tests {
         assert!(services.prepare_character_names("'Knight".to_string()).is_err());
         assert!(services.prepare_character_names("---".to_string()).is_err());
     }
+
+    #[test]
+    fn name_history_reserves_old_names_for_others_until_it_expires() {
+        let renamed_at = Timestamp::from_micros_since_unix_epoch(1_000_000);
+        let entry = CharacterNameHistoryV1 {
+            character_name_history_id: 1,
+            character_id: 7,
+            name: "sir galahad".to_string(),
+            display_name: "Sir Galahad".to_string(),
+            renamed_at,
+            reserved_until: renamed_at + Duration::from_days_ext(90),
+        };
+        let later = renamed_at + Duration::from_days_ext(30);
+
+        assert!(entry.reserves(Some(8), later));
+        assert!(entry.reserves(None, later));
+        assert!(!entry.reserves(Some(7), later));
+        assert!(!entry.reserves(None, renamed_at + Duration::from_days_ext(90)));
+    }
+
+    #[test]
+    fn name_history_expires_after_both_the_reservation_and_the_rename_cooldown() {
+        let renamed_at = Timestamp::from_micros_since_unix_epoch(1_000_000);
+        let entry = CharacterNameHistoryV1 {
+            character_name_history_id: 1,
+            character_id: 7,
+            name: "sir galahad".to_string(),
+            display_name: "Sir Galahad".to_string(),
+            renamed_at,
+            reserved_until: renamed_at + Duration::from_days_ext(10),
+        };
+        let after_reservation = renamed_at + Duration::from_days_ext(10);
+
+        assert!(!entry.is_expired(renamed_at + Duration::from_days_ext(9), Duration::ZERO));
+        assert!(entry.is_expired(after_reservation, Duration::ZERO));
+        assert!(!entry.is_expired(after_reservation, Duration::from_days_ext(30)));
+        assert!(entry.is_expired(renamed_at + Duration::from_days_ext(30), Duration::from_days_ext(30)));
+    }
 }
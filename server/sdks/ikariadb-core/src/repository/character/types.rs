@@ -1,3 +1,7 @@
+use crate::constants::{
+    DEFAULT_CHARACTER_ATTACK_SPEED, DEFAULT_CHARACTER_CAPACITY, DEFAULT_CHARACTER_EXPERIENCE, DEFAULT_CHARACTER_HEALTH,
+    DEFAULT_CHARACTER_LEVEL, DEFAULT_CHARACTER_MANA, DEFAULT_CHARACTER_SPEED,
+};
 use spacetimedb::SpacetimeType;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, SpacetimeType)]
@@ -25,3 +29,28 @@ pub enum ClassV1 {
     Warlock,
     Druid,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SpacetimeType)]
+pub struct StartingStatsV1 {
+    pub level: u16,
+    pub experience: u64,
+    pub health: u32,
+    pub mana: u32,
+    pub capacity: u32,
+    pub speed: u16,
+    pub attack_speed: u16,
+}
+
+impl Default for StartingStatsV1 {
+    fn default() -> Self {
+        Self {
+            level: DEFAULT_CHARACTER_LEVEL,
+            experience: DEFAULT_CHARACTER_EXPERIENCE,
+            health: DEFAULT_CHARACTER_HEALTH,
+            mana: DEFAULT_CHARACTER_MANA,
+            capacity: DEFAULT_CHARACTER_CAPACITY,
+            speed: DEFAULT_CHARACTER_SPEED,
+            attack_speed: DEFAULT_CHARACTER_ATTACK_SPEED,
+        }
+    }
+}
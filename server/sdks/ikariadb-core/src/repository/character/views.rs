@@ -1,6 +1,6 @@
 use crate::repository::character::{
-    CharacterStatsV1, CharacterV1, character_stats_v1__query, character_stats_v1__view, character_v1__query,
-    character_v1__view, online_character_v1__view,
+    CharacterDeletionV1, CharacterStatsV1, CharacterV1, character_deletion_v1__query, character_stats_v1__query,
+    character_stats_v1__view, character_v1__query, character_v1__view, online_character_v1__view,
 };
 use spacetimedb::{RawQuery, ViewContext, view};
 
@@ -25,3 +25,11 @@ pub fn vw_character_all_mine_v1(ctx: &ViewContext) -> RawQuery<CharacterV1> {
 pub fn vw_character_all_mine_stats_v1(ctx: &ViewContext) -> RawQuery<CharacterStatsV1> {
     ctx.from.character_stats_v1().r#where(|c| c.user_id.eq(ctx.sender())).build()
 }
+
+#[view(accessor = vw_character_all_mine_deletions_v1, public)]
+pub fn vw_character_all_mine_deletions_v1(ctx: &ViewContext) -> RawQuery<CharacterDeletionV1> {
+    ctx.from
+        .character_deletion_v1()
+        .r#where(|c| c.user_id.eq(ctx.sender()))
+        .build()
+}
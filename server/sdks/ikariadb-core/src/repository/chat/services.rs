@@ -36,6 +36,10 @@ impl Deref for ChatServices<'_> {
 
 impl ChatServices<'_> {
     pub fn send_message(&self, character_id: u64, content: String) -> ServiceResult<()> {
+        if !self.world_services().config().features.chat {
+            return Err(ChatError::chat_disabled());
+        }
+
         let content = content.trim();
         if content.is_empty() {
             return Err(ChatError::message_empty());
@@ -61,11 +65,18 @@ impl ChatServices<'_> {
 
 #[derive(Debug, Error)]
 enum ChatError {
+    #[error("Chat is disabled in this world")]
+    ChatDisabled,
+
     #[error("Chat message cannot be empty")]
     MessageEmpty,
 }
 
 impl ChatError {
+    fn chat_disabled() -> ServiceError {
+        Self::ChatDisabled.map_forbidden_error()
+    }
+
     fn message_empty() -> ServiceError {
         Self::MessageEmpty.map_validation_error()
     }
@@ -0,0 +1,61 @@
+use self::{
+    services::EnvironmentReducerContext,
+    types::{WeatherV1, WorldTimeV1},
+};
+use crate::{error::ServiceResult, extend::validate::ReducerContextRequirements};
+use spacetimedb::{ReducerContext, ScheduleAt, Timestamp, reducer, table};
+
+pub mod services;
+pub mod types;
+pub mod views;
+
+#[table(accessor = world_clock_v1, private)]
+pub struct WorldClockV1 {
+    #[primary_key]
+    pub world_clock_id: u64,
+    /// Real time the clock was started at; the world time is derived from it so missed ticks do not drift.
+    pub started_at: Timestamp,
+    /// In-game minutes per real minute, so clients can advance the clock between ticks.
+    pub speed: u32,
+    pub time: WorldTimeV1,
+    pub light_level: u8,
+}
+
+#[table(accessor = world_clock_tick_v1, private, scheduled(world_clock_tick_scheduled_v1))]
+pub struct WorldClockTickV1 {
+    #[auto_inc]
+    #[primary_key]
+    pub tick_id: u64,
+    pub scheduled_at: ScheduleAt,
+}
+
+#[reducer]
+pub fn world_clock_tick_scheduled_v1(ctx: &ReducerContext, _timer: WorldClockTickV1) -> ServiceResult<()> {
+    ctx.require_internal_access()?;
+    ctx.environment_services().tick_world_clock();
+    Ok(())
+}
+
+/// Weather of a column of sectors, keyed by the sector key at ground level; underground is always clear.
+#[table(accessor = sector_weather_v1, private)]
+pub struct SectorWeatherV1 {
+    #[primary_key]
+    pub sector_key: u64,
+    pub weather: WeatherV1,
+    pub changed_at: Timestamp,
+}
+
+#[table(accessor = weather_tick_v1, private, scheduled(weather_tick_scheduled_v1))]
+pub struct WeatherTickV1 {
+    #[auto_inc]
+    #[primary_key]
+    pub tick_id: u64,
+    pub scheduled_at: ScheduleAt,
+}
+
+#[reducer]
+pub fn weather_tick_scheduled_v1(ctx: &ReducerContext, _timer: WeatherTickV1) -> ServiceResult<()> {
+    ctx.require_internal_access()?;
+    ctx.environment_services().tick_weather();
+    Ok(())
+}
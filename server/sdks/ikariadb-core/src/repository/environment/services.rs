@@ -0,0 +1,138 @@
+use crate::{
+    constants::{WEATHER_TICK_SECS, WORLD_CLOCK_ID, WORLD_CLOCK_SPEED, WORLD_CLOCK_START_HOUR, WORLD_CLOCK_TICK_SECS},
+    extend::stdb::ReducerContextExt,
+    repository::{
+        environment::{
+            SectorWeatherV1, WeatherTickV1, WorldClockTickV1, WorldClockV1, sector_weather_v1,
+            types::{WeatherV1, WorldTimeV1, weather_sector_key},
+            weather_tick_v1, world_clock_tick_v1, world_clock_v1,
+        },
+        world::types::Vec3,
+    },
+};
+use spacetimedb::{ReducerContext, ScheduleAt, Table};
+use std::{ops::Deref, time::Duration};
+
+pub trait EnvironmentReducerContext {
+    fn environment_services(&self) -> EnvironmentServices<'_>;
+}
+
+impl EnvironmentReducerContext for ReducerContext {
+    fn environment_services(&self) -> EnvironmentServices<'_> {
+        EnvironmentServices { ctx: self }
+    }
+}
+
+pub struct EnvironmentServices<'a> {
+    ctx: &'a ReducerContext,
+}
+
+impl Deref for EnvironmentServices<'_> {
+    type Target = ReducerContext;
+
+    fn deref(&self) -> &Self::Target {
+        self.ctx
+    }
+}
+
+impl EnvironmentServices<'_> {
+    /// Creates the clock on first publish and makes sure its tick is scheduled; an existing clock keeps running.
+    pub fn start_world_clock(&self) {
+        if self.find_world_clock().is_none() {
+            let time = WorldTimeV1::after(Duration::ZERO, WORLD_CLOCK_SPEED, WORLD_CLOCK_START_HOUR);
+            self.db.world_clock_v1().insert(WorldClockV1 {
+                world_clock_id: WORLD_CLOCK_ID,
+                started_at: self.timestamp,
+                speed: WORLD_CLOCK_SPEED,
+                time,
+                light_level: time.light_level(),
+            });
+        }
+
+        if self.db.world_clock_tick_v1().count() == 0 {
+            self.db.world_clock_tick_v1().insert(WorldClockTickV1 {
+                tick_id: 0,
+                scheduled_at: ScheduleAt::Interval(Duration::from_secs(WORLD_CLOCK_TICK_SECS).into()),
+            });
+        }
+    }
+
+    pub fn find_world_clock(&self) -> Option<WorldClockV1> {
+        self.db.world_clock_v1().world_clock_id().find(WORLD_CLOCK_ID)
+    }
+
+    /// Current world time, exact to the reducer's timestamp rather than the last tick.
+    pub fn world_time(&self) -> WorldTimeV1 {
+        match self.find_world_clock() {
+            Some(clock) => self.world_time_of(&clock),
+            None => WorldTimeV1::after(Duration::ZERO, WORLD_CLOCK_SPEED, WORLD_CLOCK_START_HOUR),
+        }
+    }
+
+    pub fn tick_world_clock(&self) {
+        let Some(clock) = self.find_world_clock() else {
+            return;
+        };
+
+        let time = self.world_time_of(&clock);
+        if time == clock.time {
+            return;
+        }
+        self.db.world_clock_v1().world_clock_id().update(WorldClockV1 {
+            time,
+            light_level: time.light_level(),
+            ..clock
+        });
+    }
+
+    fn world_time_of(&self, clock: &WorldClockV1) -> WorldTimeV1 {
+        let elapsed = self.timestamp.duration_since(clock.started_at).unwrap_or_default();
+        WorldTimeV1::after(elapsed, clock.speed, WORLD_CLOCK_START_HOUR)
+    }
+
+    pub fn start_weather(&self) {
+        if self.db.weather_tick_v1().count() == 0 {
+            self.db.weather_tick_v1().insert(WeatherTickV1 {
+                tick_id: 0,
+                scheduled_at: ScheduleAt::Interval(Duration::from_secs(WEATHER_TICK_SECS).into()),
+            });
+        }
+    }
+
+    pub fn weather_at(&self, pos: Vec3) -> WeatherV1 {
+        weather_sector_key(pos)
+            .and_then(|sector_key| self.db.sector_weather_v1().sector_key().find(sector_key))
+            .map(|sector| sector.weather)
+            .unwrap_or_default()
+    }
+
+    /// Starts tracking the weather of the sector column above `pos` once ground-level map is placed there.
+    pub fn track_weather_at(&self, pos: Vec3) {
+        let Some(sector_key) = weather_sector_key(pos) else {
+            return;
+        };
+        if self.db.sector_weather_v1().sector_key().find(sector_key).is_none() {
+            self.db.sector_weather_v1().insert(SectorWeatherV1 {
+                sector_key,
+                weather: WeatherV1::default(),
+                changed_at: self.timestamp,
+            });
+        }
+    }
+
+    /// Rolls the next weather of every tracked sector.
+    pub fn tick_weather(&self) {
+        let sectors: Vec<SectorWeatherV1> = self.db.sector_weather_v1().iter().collect();
+        for sector in sectors {
+            let roll = self.random_index(100).unwrap_or_default() as u8;
+            let weather = sector.weather.next(roll);
+            if weather != sector.weather {
+                self.db.sector_weather_v1().sector_key().update(SectorWeatherV1 {
+                    weather,
+                    changed_at: self.timestamp,
+                    ..sector
+                });
+            }
+        }
+    }
+}
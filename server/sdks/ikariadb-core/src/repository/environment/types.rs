@@ -0,0 +1,181 @@
+use crate::repository::world::types::Vec3;
+use ikaria_shared::constants::GROUND_LEVEL;
+use spacetimedb::SpacetimeType;
+use std::time::Duration;
+
+pub const MINUTES_PER_HOUR: u16 = 60;
+pub const MINUTES_PER_DAY: u16 = 24 * MINUTES_PER_HOUR;
+
+const DAWN_START_HOUR: u16 = 6;
+const DAY_START_HOUR: u16 = 8;
+const DUSK_START_HOUR: u16 = 18;
+const NIGHT_START_HOUR: u16 = 20;
+
+pub const DAYLIGHT_LEVEL: u8 = 255;
+pub const NIGHT_LIGHT_LEVEL: u8 = 40;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, SpacetimeType)]
+pub struct WorldTimeV1 {
+    pub day: u32,
+    pub minute_of_day: u16,
+}
+
+impl WorldTimeV1 {
+    /// World time after `elapsed` real time at `speed` in-game minutes per real minute, starting at `start_hour`.
+    pub fn after(elapsed: Duration, speed: u32, start_hour: u16) -> Self {
+        let world_minutes = elapsed.as_secs() * speed as u64 / 60 + (start_hour * MINUTES_PER_HOUR) as u64;
+        Self {
+            day: (world_minutes / MINUTES_PER_DAY as u64).min(u32::MAX as u64) as u32,
+            minute_of_day: (world_minutes % MINUTES_PER_DAY as u64) as u16,
+        }
+    }
+
+    pub fn hour(&self) -> u16 {
+        self.minute_of_day / MINUTES_PER_HOUR
+    }
+
+    pub fn minute(&self) -> u16 {
+        self.minute_of_day % MINUTES_PER_HOUR
+    }
+
+    pub fn is_night(&self) -> bool {
+        let hour = self.hour();
+        !(DAWN_START_HOUR..NIGHT_START_HOUR).contains(&hour)
+    }
+
+    /// Ambient light: full daylight, dark nights, and linear ramps through dawn and dusk.
+    pub fn light_level(&self) -> u8 {
+        let dawn = DAWN_START_HOUR * MINUTES_PER_HOUR;
+        let day = DAY_START_HOUR * MINUTES_PER_HOUR;
+        let dusk = DUSK_START_HOUR * MINUTES_PER_HOUR;
+        let night = NIGHT_START_HOUR * MINUTES_PER_HOUR;
+        let minute = self.minute_of_day;
+
+        let ramp = |from: u16, to: u16, brightening: bool| {
+            let progress = (minute - from) as u32 * 255 / (to - from) as u32;
+            let progress = if brightening { progress } else { 255 - progress };
+            let span = (DAYLIGHT_LEVEL - NIGHT_LIGHT_LEVEL) as u32;
+            NIGHT_LIGHT_LEVEL + (span * progress / 255) as u8
+        };
+
+        match minute {
+            m if m < dawn || m >= night => NIGHT_LIGHT_LEVEL,
+            m if m < day => ramp(dawn, day, true),
+            m if m < dusk => DAYLIGHT_LEVEL,
+            _ => ramp(dusk, night, false),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, SpacetimeType)]
+pub enum WeatherV1 {
+    #[default]
+    Clear,
+    Rain,
+    Fog,
+    Storm,
+}
+
+impl WeatherV1 {
+    /// Next weather for a `roll` in `0..100`; weather drifts through rain before it turns into a storm.
+    pub fn next(&self, roll: u8) -> WeatherV1 {
+        match (self, roll) {
+            (WeatherV1::Clear, 0..70) => WeatherV1::Clear,
+            (WeatherV1::Clear, 70..85) => WeatherV1::Fog,
+            (WeatherV1::Clear, _) => WeatherV1::Rain,
+            (WeatherV1::Fog, 0..50) => WeatherV1::Clear,
+            (WeatherV1::Fog, 50..80) => WeatherV1::Fog,
+            (WeatherV1::Fog, _) => WeatherV1::Rain,
+            (WeatherV1::Rain, 0..35) => WeatherV1::Clear,
+            (WeatherV1::Rain, 35..70) => WeatherV1::Rain,
+            (WeatherV1::Rain, 70..80) => WeatherV1::Fog,
+            (WeatherV1::Rain, _) => WeatherV1::Storm,
+            (WeatherV1::Storm, 0..40) => WeatherV1::Rain,
+            (WeatherV1::Storm, 40..70) => WeatherV1::Storm,
+            (WeatherV1::Storm, _) => WeatherV1::Clear,
+        }
+    }
+
+    /// Share of the usual sight radius characters can be seen at.
+    pub fn sight_percent(&self) -> u16 {
+        match self {
+            WeatherV1::Clear => 100,
+            WeatherV1::Rain => 80,
+            WeatherV1::Fog => 40,
+            WeatherV1::Storm => 60,
+        }
+    }
+
+    /// Scales a tile's step time percentage; wet and windy weather slows walkers down.
+    pub fn scale_step_time(&self, step_time_percent: u16) -> u16 {
+        let factor: u32 = match self {
+            WeatherV1::Clear | WeatherV1::Fog => 100,
+            WeatherV1::Rain => 110,
+            WeatherV1::Storm => 125,
+        };
+        (step_time_percent as u32 * factor / 100).min(u16::MAX as u32) as u16
+    }
+}
+
+/// Weather is shared by every floor from ground level up, so it is keyed by the ground-level sector.
+pub fn weather_sector_key(pos: Vec3) -> Option<u64> {
+    (pos.z >= GROUND_LEVEL).then(|| Vec3::new(pos.x, pos.y, GROUND_LEVEL).sector_key())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u16, minute: u16) -> WorldTimeV1 {
+        WorldTimeV1 {
+            day: 0,
+            minute_of_day: hour * MINUTES_PER_HOUR + minute,
+        }
+    }
+
+    #[test]
+    fn world_time_runs_faster_than_real_time() {
+        let time = WorldTimeV1::after(Duration::from_secs(150), 24, 8);
+        assert_eq!((time.day, time.hour(), time.minute()), (0, 9, 0));
+
+        let time = WorldTimeV1::after(Duration::from_secs(3600), 24, 8);
+        assert_eq!((time.day, time.hour(), time.minute()), (1, 8, 0));
+    }
+
+    #[test]
+    fn night_lasts_from_dusk_end_to_dawn() {
+        assert!(at(20, 0).is_night());
+        assert!(at(3, 0).is_night());
+        assert!(at(5, 59).is_night());
+        assert!(!at(6, 0).is_night());
+        assert!(!at(19, 59).is_night());
+    }
+
+    #[test]
+    fn light_level_ramps_through_dawn_and_dusk() {
+        assert_eq!(at(2, 0).light_level(), NIGHT_LIGHT_LEVEL);
+        assert_eq!(at(6, 0).light_level(), NIGHT_LIGHT_LEVEL);
+        assert!(at(7, 0).light_level() > NIGHT_LIGHT_LEVEL && at(7, 0).light_level() < DAYLIGHT_LEVEL);
+        assert_eq!(at(12, 0).light_level(), DAYLIGHT_LEVEL);
+        assert_eq!(at(18, 0).light_level(), DAYLIGHT_LEVEL);
+        assert!(at(19, 0).light_level() < DAYLIGHT_LEVEL);
+        assert_eq!(at(20, 0).light_level(), NIGHT_LIGHT_LEVEL);
+    }
+
+    #[test]
+    fn weather_turns_into_storm_only_from_rain() {
+        assert_eq!(WeatherV1::Clear.next(0), WeatherV1::Clear);
+        assert_eq!(WeatherV1::Clear.next(99), WeatherV1::Rain);
+        assert_eq!(WeatherV1::Rain.next(99), WeatherV1::Storm);
+        assert_eq!(WeatherV1::Storm.next(99), WeatherV1::Clear);
+        assert!((0..100).all(|roll| WeatherV1::Clear.next(roll) != WeatherV1::Storm));
+        assert!((0..100).all(|roll| WeatherV1::Fog.next(roll) != WeatherV1::Storm));
+    }
+
+    #[test]
+    fn weather_scales_step_time() {
+        assert_eq!(WeatherV1::Clear.scale_step_time(80), 80);
+        assert_eq!(WeatherV1::Rain.scale_step_time(100), 110);
+        assert_eq!(WeatherV1::Storm.scale_step_time(160), 200);
+    }
+}
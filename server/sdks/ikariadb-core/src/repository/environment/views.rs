@@ -0,0 +1,31 @@
+use crate::{
+    constants::{MAP_VIEW_RADIUS, WORLD_CLOCK_ID},
+    extend::proximity::find_viewer,
+    repository::{
+        environment::{SectorWeatherV1, WorldClockV1, sector_weather_v1__view, world_clock_v1__view},
+        world::sector_grid::SectorGrid,
+    },
+};
+use ikaria_shared::constants::GROUND_LEVEL;
+use spacetimedb::{ViewContext, view};
+
+#[view(accessor = vw_environment_world_clock_v1, public)]
+pub fn vw_environment_world_clock_v1(ctx: &ViewContext) -> Option<WorldClockV1> {
+    ctx.db.world_clock_v1().world_clock_id().find(WORLD_CLOCK_ID)
+}
+
+/// Weather of the sectors around the current character; empty underground.
+#[view(accessor = vw_environment_weather_v1, public)]
+pub fn vw_environment_weather_v1(ctx: &ViewContext) -> Vec<SectorWeatherV1> {
+    let Some(viewer) = find_viewer(ctx) else {
+        return Vec::new();
+    };
+    if viewer.z < GROUND_LEVEL {
+        return Vec::new();
+    }
+
+    SectorGrid::radius(viewer.into(), MAP_VIEW_RADIUS, GROUND_LEVEL..=GROUND_LEVEL)
+        .sector_keys()
+        .filter_map(|sector_key| ctx.db.sector_weather_v1().sector_key().find(sector_key))
+        .collect()
+}
@@ -6,6 +6,8 @@ use crate::{
             OneshotDeferredEventV1, oneshot_deferred_event_v1,
             types::{DeferredEventV1, EventV1},
         },
+        house::services::HouseReducerContext,
+        progression::services::ProgressionReducerContext,
         user::services::UserReducerContext,
         world::services::WorldReducerContext,
     },
@@ -46,7 +48,7 @@ impl EventServices<'_> {
     fn handle_sync_event(&self, event: EventV1, _rethrow: bool) -> ServiceResult<()> {
         match event {
             EventV1::SystemInit => {
-                self.world_services().seed_initial_map();
+                self.user_services().grant_game_master(self.sender());
             },
             EventV1::UserCreated { .. } => {},
             EventV1::UserSignedIn { user_id } => {
@@ -67,6 +69,11 @@ impl EventServices<'_> {
                 self.world_services().despawn_character(user_id);
                 self.character_services().clear_online_character(user_id);
             },
+            EventV1::CharacterDeleted { character_id, .. } => {
+                self.house_services().forget_character(character_id);
+                self.progression_services().forget_character(character_id);
+                self.world_services().forget_character(character_id);
+            },
         }
 
         Ok(())
@@ -13,6 +13,7 @@ pub enum EventV1 {
     CharacterCreated { user_id: Identity, character_id: u64 },
     CharacterSelected { user_id: Identity, character_id: u64 },
     CharacterUnselected { user_id: Identity },
+    CharacterDeleted { user_id: Identity, character_id: u64 },
 }
 
 #[derive(Debug, Clone, Copy, SpacetimeType)]
@@ -59,4 +60,9 @@ impl EventPublisher<'_> {
     pub fn character_unselected(&self, user_id: Identity) -> ServiceResult<()> {
         self.event_services().fire(EventV1::CharacterUnselected { user_id })
     }
+
+    pub fn character_deleted(&self, user_id: Identity, character_id: u64) -> ServiceResult<()> {
+        self.event_services()
+            .fire(EventV1::CharacterDeleted { user_id, character_id })
+    }
 }
@@ -0,0 +1,62 @@
+use self::{services::HouseReducerContext, types::HouseRoleV1};
+use crate::{error::ServiceResult, extend::validate::ReducerContextRequirements};
+use spacetimedb::{ReducerContext, ScheduleAt, Timestamp, reducer, table};
+
+pub mod reducers;
+pub mod services;
+pub mod types;
+pub mod views;
+
+/// A rentable area of the map, also marked with the `house` region flag.
+#[table(accessor = house_v1, private)]
+pub struct HouseV1 {
+    #[auto_inc]
+    #[primary_key]
+    pub house_id: u64,
+    #[unique]
+    pub name: String,
+    pub x1: u16,
+    pub y1: u16,
+    pub x2: u16,
+    pub y2: u16,
+    #[index(btree)]
+    pub z: u8,
+    pub rent: u64,
+    /// `None` while nobody rents the house.
+    pub paid_until: Option<Timestamp>,
+}
+
+/// Owner, subowners and guests of a house; only they can pass its doors.
+#[table(accessor = house_access_v1, private)]
+pub struct HouseAccessV1 {
+    #[auto_inc]
+    #[primary_key]
+    pub house_access_id: u64,
+    #[index(btree)]
+    pub house_id: u64,
+    #[index(btree)]
+    pub character_id: u64,
+    pub role: HouseRoleV1,
+}
+
+#[table(accessor = house_door_v1, private)]
+pub struct HouseDoorV1 {
+    #[primary_key]
+    pub map_id: u64,
+    #[index(btree)]
+    pub house_id: u64,
+}
+
+#[table(accessor = house_rent_due_v1, private, scheduled(house_rent_due_scheduled_v1))]
+pub struct HouseRentDueV1 {
+    #[primary_key]
+    pub house_id: u64,
+    pub scheduled_at: ScheduleAt,
+}
+
+#[reducer]
+pub fn house_rent_due_scheduled_v1(ctx: &ReducerContext, timer: HouseRentDueV1) -> ServiceResult<()> {
+    ctx.require_internal_access()?;
+    ctx.house_services().collect_rent(timer.house_id);
+    Ok(())
+}
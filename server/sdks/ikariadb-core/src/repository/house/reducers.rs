@@ -0,0 +1,76 @@
+use crate::{
+    error::ServiceResult,
+    extend::validate::ReducerContextRequirements,
+    repository::{
+        house::{services::HouseReducerContext, types::HouseRoleV1},
+        world::types::{Rect, Vec3},
+    },
+};
+use spacetimedb::{ReducerContext, reducer};
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+pub fn create_house_v1(
+    ctx: &ReducerContext,
+    name: String,
+    x1: u16,
+    y1: u16,
+    x2: u16,
+    y2: u16,
+    z: u8,
+    rent: u64,
+) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.house_services().create_house(&name, Rect::new(x1, y1, x2, y2), z, rent)?;
+    Ok(())
+}
+
+#[reducer]
+pub fn remove_house_v1(ctx: &ReducerContext, house_id: u64) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.house_services().remove_house(house_id)?;
+    Ok(())
+}
+
+#[reducer]
+pub fn add_house_door_v1(ctx: &ReducerContext, house_id: u64, x: u16, y: u16, z: u8) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.house_services().add_door(house_id, Vec3::new(x, y, z))?;
+    Ok(())
+}
+
+#[reducer]
+pub fn remove_house_door_v1(ctx: &ReducerContext, x: u16, y: u16, z: u8) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.house_services().remove_door(Vec3::new(x, y, z))?;
+    Ok(())
+}
+
+#[reducer]
+pub fn rent_house_v1(ctx: &ReducerContext, house_id: u64) -> ServiceResult<()> {
+    let character = ctx.require_online()?;
+    ctx.house_services().rent_house(character.character_id, house_id)?;
+    Ok(())
+}
+
+#[reducer]
+pub fn pay_house_rent_v1(ctx: &ReducerContext, house_id: u64) -> ServiceResult<()> {
+    let character = ctx.require_online()?;
+    ctx.house_services().pay_rent(character.character_id, house_id)?;
+    Ok(())
+}
+
+#[reducer]
+pub fn invite_to_house_v1(ctx: &ReducerContext, house_id: u64, character_id: u64, role: HouseRoleV1) -> ServiceResult<()> {
+    let character = ctx.require_online()?;
+    ctx.house_services()
+        .invite(character.character_id, house_id, character_id, role)?;
+    Ok(())
+}
+
+#[reducer]
+pub fn kick_from_house_v1(ctx: &ReducerContext, house_id: u64, character_id: u64) -> ServiceResult<()> {
+    let character = ctx.require_online()?;
+    ctx.house_services().kick(character.character_id, house_id, character_id)?;
+    Ok(())
+}
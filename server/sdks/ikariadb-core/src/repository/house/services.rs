@@ -0,0 +1,474 @@
+use crate::{
+    constants::{HOUSE_NAME_MAX_LEN, HOUSE_NAME_MIN_LEN, HOUSE_RENT_PERIOD_DAYS},
+    error::{ErrorMapper, ServiceError, ServiceResult},
+    extend::{duration::DurationExt, validate::ReducerContextRequirements},
+    repository::{
+        character::services::CharacterReducerContext,
+        house::{
+            HouseAccessV1, HouseDoorV1, HouseRentDueV1, HouseV1, house_access_v1, house_door_v1, house_rent_due_v1, house_v1,
+            types::{HouseRoleV1, next_paid_until},
+        },
+        world::{
+            online_character_position_v1,
+            services::WorldReducerContext,
+            types::{Rect, RegionFlagsV1, Vec2, Vec3},
+        },
+    },
+};
+use log::info;
+use spacetimedb::{ReducerContext, Table};
+use std::{ops::Deref, time::Duration};
+use thiserror::Error;
+
+const HOUSE_REGION_FLAGS: RegionFlagsV1 = RegionFlagsV1 {
+    protection_zone: false,
+    no_pvp: false,
+    no_logout: false,
+    house: true,
+};
+
+pub trait HouseReducerContext {
+    fn house_services(&self) -> HouseServices<'_>;
+}
+
+impl HouseReducerContext for ReducerContext {
+    fn house_services(&self) -> HouseServices<'_> {
+        HouseServices { ctx: self }
+    }
+}
+
+pub struct HouseServices<'a> {
+    ctx: &'a ReducerContext,
+}
+
+impl Deref for HouseServices<'_> {
+    type Target = ReducerContext;
+
+    fn deref(&self) -> &Self::Target {
+        self.ctx
+    }
+}
+
+impl HouseServices<'_> {
+    pub fn find_house(&self, house_id: u64) -> Option<HouseV1> {
+        self.db.house_v1().house_id().find(house_id)
+    }
+
+    pub fn get_house(&self, house_id: u64) -> ServiceResult<HouseV1> {
+        self.find_house(house_id).ok_or_else(|| HouseError::house_not_found(house_id))
+    }
+
+    pub fn find_house_at(&self, pos: Vec3) -> Option<HouseV1> {
+        self.db
+            .house_v1()
+            .z()
+            .filter(pos.z)
+            .find(|house| Rect::from(house).contains(pos.into()))
+    }
+
+    pub fn find_role(&self, house_id: u64, character_id: u64) -> Option<HouseRoleV1> {
+        self.find_access(house_id, character_id).map(|access| access.role)
+    }
+
+    fn find_access(&self, house_id: u64, character_id: u64) -> Option<HouseAccessV1> {
+        self.db
+            .house_access_v1()
+            .character_id()
+            .filter(character_id)
+            .find(|access| access.house_id == house_id)
+    }
+
+    fn find_owner(&self, house_id: u64) -> Option<HouseAccessV1> {
+        self.db
+            .house_access_v1()
+            .house_id()
+            .filter(house_id)
+            .find(|access| access.role == HouseRoleV1::Owner)
+    }
+
+    pub fn can_pass_door(&self, character_id: u64, pos: Vec3) -> bool {
+        self.db
+            .house_door_v1()
+            .map_id()
+            .find(pos.map_id())
+            .is_none_or(|door| self.find_access(door.house_id, character_id).is_some())
+    }
+
+    pub fn ensure_can_pass_door(&self, character_id: u64, pos: Vec3) -> ServiceResult<()> {
+        if !self.can_pass_door(character_id, pos) {
+            return Err(HouseError::door_locked());
+        }
+        Ok(())
+    }
+
+    /// Characters only stay on house tiles while they are on its access list.
+    pub fn can_stay_at(&self, character_id: u64, pos: Vec3) -> bool {
+        self.find_house_at(pos)
+            .is_none_or(|house| self.find_access(house.house_id, character_id).is_some())
+    }
+
+    /// Creates an unrented house and adds the `house` flag to its area, on top of any flags the area already has.
+    pub fn create_house(&self, name: &str, rect: Rect, z: u8, rent: u64) -> ServiceResult<HouseV1> {
+        let name = name.trim().to_string();
+        self.validate_str(&name, "name", HOUSE_NAME_MIN_LEN as u64, HOUSE_NAME_MAX_LEN as u64)?;
+        if self.db.house_v1().name().find(&name).is_some() {
+            return Err(HouseError::name_taken(name));
+        }
+        if self
+            .db
+            .house_v1()
+            .z()
+            .filter(z)
+            .any(|house| Rect::from(&house).overlaps(&rect))
+        {
+            return Err(HouseError::house_overlaps());
+        }
+
+        self.world_services().add_region_flags(rect, z, HOUSE_REGION_FLAGS)?;
+
+        let house = self.db.house_v1().insert(HouseV1 {
+            house_id: 0,
+            name,
+            x1: rect.min.x,
+            y1: rect.min.y,
+            x2: rect.max.x,
+            y2: rect.max.y,
+            z,
+            rent,
+            paid_until: None,
+        });
+        info!("House {} '{}' created", house.house_id, house.name);
+        Ok(house)
+    }
+
+    pub fn remove_house(&self, house_id: u64) -> ServiceResult<()> {
+        let house = self.get_house(house_id)?;
+        self.clear_access(house_id);
+        let doors: Vec<u64> = self
+            .db
+            .house_door_v1()
+            .house_id()
+            .filter(house_id)
+            .map(|door| door.map_id)
+            .collect();
+        for map_id in doors {
+            self.db.house_door_v1().map_id().delete(map_id);
+        }
+        self.db.house_rent_due_v1().house_id().delete(house_id);
+        self.world_services()
+            .remove_region_flags(Rect::from(&house), house.z, HOUSE_REGION_FLAGS)?;
+        self.db.house_v1().house_id().delete(house_id);
+        Ok(())
+    }
+
+    pub fn add_door(&self, house_id: u64, pos: Vec3) -> ServiceResult<()> {
+        self.get_house(house_id)?;
+        if self.world_services().find_map_at(pos).is_none() {
+            return Err(HouseError::door_off_map(pos));
+        }
+        if self.db.house_door_v1().map_id().find(pos.map_id()).is_some() {
+            return Err(HouseError::door_taken(pos));
+        }
+        self.db.house_door_v1().insert(HouseDoorV1 {
+            map_id: pos.map_id(),
+            house_id,
+        });
+        Ok(())
+    }
+
+    pub fn remove_door(&self, pos: Vec3) -> ServiceResult<()> {
+        if !self.db.house_door_v1().map_id().delete(pos.map_id()) {
+            return Err(HouseError::door_not_found(pos));
+        }
+        Ok(())
+    }
+
+    pub fn remove_doors_in(&self, rect: Rect, z: u8) {
+        let doors: Vec<u64> = self
+            .db
+            .house_door_v1()
+            .iter()
+            .map(|door| door.map_id)
+            .filter(|&map_id| {
+                let pos = Vec3::from_map_id(map_id);
+                pos.z == z && rect.contains(pos.into())
+            })
+            .collect();
+        for map_id in doors {
+            self.db.house_door_v1().map_id().delete(map_id);
+        }
+    }
+
+    /// Removes doors whose tile is gone, e.g. after a map re-import.
+    pub fn drop_doors_off_map(&self) {
+        let detached: Vec<u64> = self
+            .db
+            .house_door_v1()
+            .iter()
+            .filter(|door| self.world_services().find_map_at(Vec3::from_map_id(door.map_id)).is_none())
+            .map(|door| door.map_id)
+            .collect();
+        for map_id in detached {
+            self.db.house_door_v1().map_id().delete(map_id);
+        }
+    }
+
+    pub fn rent_house(&self, character_id: u64, house_id: u64) -> ServiceResult<()> {
+        self.get_house(house_id)?;
+        if self.find_owner(house_id).is_some() {
+            return Err(HouseError::house_already_rented());
+        }
+        let owns_house = self
+            .db
+            .house_access_v1()
+            .character_id()
+            .filter(character_id)
+            .any(|access| access.role == HouseRoleV1::Owner);
+        if owns_house {
+            return Err(HouseError::already_owns_house());
+        }
+
+        self.set_access(house_id, character_id, HouseRoleV1::Owner);
+        self.pay_rent(character_id, house_id)
+    }
+
+    /// Extends the rent by one period and moves the eviction to the new due date.
+    pub fn pay_rent(&self, character_id: u64, house_id: u64) -> ServiceResult<()> {
+        let mut house = self.get_house(house_id)?;
+        if self.find_role(house_id, character_id) != Some(HouseRoleV1::Owner) {
+            return Err(HouseError::not_house_owner());
+        }
+
+        // Characters do not carry gold yet, so paying only moves the due date; `house.rent` is charged once they do.
+        let period = Duration::from_days_ext(HOUSE_RENT_PERIOD_DAYS);
+        let paid_until = next_paid_until(house.paid_until, self.timestamp, period);
+        house.paid_until = Some(paid_until);
+        self.db.house_v1().house_id().update(house);
+        self.db.house_rent_due_v1().house_id().insert_or_update(HouseRentDueV1 {
+            house_id,
+            scheduled_at: paid_until.into(),
+        });
+        Ok(())
+    }
+
+    pub fn invite(&self, actor_id: u64, house_id: u64, character_id: u64, role: HouseRoleV1) -> ServiceResult<()> {
+        self.get_house(house_id)?;
+        self.character_services().get_offline(character_id)?;
+
+        let actor_role = self.find_role(house_id, actor_id);
+        let current_role = self.find_role(house_id, character_id);
+        let allowed = actor_role.is_some_and(|actor_role| {
+            actor_role.can_manage(role) && current_role.is_none_or(|current_role| actor_role.can_manage(current_role))
+        });
+        if !allowed {
+            return Err(HouseError::insufficient_role());
+        }
+
+        self.set_access(house_id, character_id, role);
+        Ok(())
+    }
+
+    pub fn kick(&self, actor_id: u64, house_id: u64, character_id: u64) -> ServiceResult<()> {
+        let house = self.get_house(house_id)?;
+        let Some(access) = self.find_access(house_id, character_id) else {
+            return Err(HouseError::not_invited(character_id));
+        };
+        if !self
+            .find_role(house_id, actor_id)
+            .is_some_and(|actor_role| actor_role.can_manage(access.role))
+        {
+            return Err(HouseError::insufficient_role());
+        }
+
+        self.db.house_access_v1().house_access_id().delete(access.house_access_id);
+        self.expel_intruders(&house);
+        Ok(())
+    }
+
+    /// Evicts the owner once the paid period is over; paying in the meantime moves the due date instead.
+    pub fn collect_rent(&self, house_id: u64) {
+        let Some(house) = self.find_house(house_id) else {
+            return;
+        };
+        if house.paid_until.is_some_and(|paid_until| paid_until > self.timestamp) {
+            return;
+        }
+        self.evict(house);
+    }
+
+    fn evict(&self, mut house: HouseV1) {
+        info!("House {} '{}' evicted for unpaid rent", house.house_id, house.name);
+        self.clear_access(house.house_id);
+        house.paid_until = None;
+        let house = self.db.house_v1().house_id().update(house);
+        self.expel_intruders(&house);
+        // Items are not placed on the map yet; once they are, whatever is left inside goes to the owner's depot.
+    }
+
+    /// Drops every house access of a deleted character; houses it owned are evicted as if the rent went unpaid.
+    pub fn forget_character(&self, character_id: u64) {
+        let accesses: Vec<HouseAccessV1> = self.db.house_access_v1().character_id().filter(character_id).collect();
+        for access in accesses {
+            if access.role == HouseRoleV1::Owner
+                && let Some(house) = self.find_house(access.house_id)
+            {
+                self.evict(house);
+            } else {
+                self.db.house_access_v1().house_access_id().delete(access.house_access_id);
+            }
+        }
+    }
+
+    fn set_access(&self, house_id: u64, character_id: u64, role: HouseRoleV1) {
+        match self.find_access(house_id, character_id) {
+            Some(access) => {
+                self.db
+                    .house_access_v1()
+                    .house_access_id()
+                    .update(HouseAccessV1 { role, ..access });
+            },
+            None => {
+                self.db.house_access_v1().insert(HouseAccessV1 {
+                    house_access_id: 0,
+                    house_id,
+                    character_id,
+                    role,
+                });
+            },
+        }
+    }
+
+    fn clear_access(&self, house_id: u64) {
+        let accesses: Vec<u64> = self
+            .db
+            .house_access_v1()
+            .house_id()
+            .filter(house_id)
+            .map(|access| access.house_access_id)
+            .collect();
+        for house_access_id in accesses {
+            self.db.house_access_v1().house_access_id().delete(house_access_id);
+        }
+    }
+
+    /// Moves online characters that lost access out of the house, next to one of its doors.
+    fn expel_intruders(&self, house: &HouseV1) {
+        let rect = Rect::from(house);
+        let exit = self
+            .db
+            .house_door_v1()
+            .house_id()
+            .filter(house.house_id)
+            .next()
+            .map(|door| Vec3::from_map_id(door.map_id));
+
+        let intruders: Vec<u64> = self
+            .db
+            .online_character_position_v1()
+            .iter()
+            .filter(|position| {
+                position.z == house.z
+                    && rect.contains(Vec2::new(position.x, position.y))
+                    && self.find_access(house.house_id, position.character_id).is_none()
+            })
+            .map(|position| position.character_id)
+            .collect();
+        for character_id in intruders {
+            if let Err(error) = self.world_services().move_character_out(character_id, rect, exit) {
+                info!(
+                    "Could not move character {character_id} out of house {}: {error}",
+                    house.house_id
+                );
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+enum HouseError {
+    #[error("House {0} was not found")]
+    HouseNotFound(u64),
+
+    #[error("House name '{0}' is already taken")]
+    NameTaken(String),
+
+    #[error("House overlaps another house")]
+    HouseOverlaps,
+
+    #[error("House is already rented")]
+    HouseAlreadyRented,
+
+    #[error("Character already owns a house")]
+    AlreadyOwnsHouse,
+
+    #[error("Only the house owner can do that")]
+    NotHouseOwner,
+
+    #[error("House role is not high enough")]
+    InsufficientRole,
+
+    #[error("Character {0} is not on the house access list")]
+    NotInvited(u64),
+
+    #[error("Door is locked")]
+    DoorLocked,
+
+    #[error("No map tile for a door at ({}, {}, {})", .0.x, .0.y, .0.z)]
+    DoorOffMap(Vec3),
+
+    #[error("There is already a door at ({}, {}, {})", .0.x, .0.y, .0.z)]
+    DoorTaken(Vec3),
+
+    #[error("No door at ({}, {}, {})", .0.x, .0.y, .0.z)]
+    DoorNotFound(Vec3),
+}
+
+impl HouseError {
+    fn house_not_found(house_id: u64) -> ServiceError {
+        Self::HouseNotFound(house_id).map_not_found_error()
+    }
+
+    fn name_taken(name: String) -> ServiceError {
+        Self::NameTaken(name).map_conflict_error()
+    }
+
+    fn house_overlaps() -> ServiceError {
+        Self::HouseOverlaps.map_conflict_error()
+    }
+
+    fn house_already_rented() -> ServiceError {
+        Self::HouseAlreadyRented.map_conflict_error()
+    }
+
+    fn already_owns_house() -> ServiceError {
+        Self::AlreadyOwnsHouse.map_validation_error()
+    }
+
+    fn not_house_owner() -> ServiceError {
+        Self::NotHouseOwner.map_forbidden_error()
+    }
+
+    fn insufficient_role() -> ServiceError {
+        Self::InsufficientRole.map_forbidden_error()
+    }
+
+    fn not_invited(character_id: u64) -> ServiceError {
+        Self::NotInvited(character_id).map_not_found_error()
+    }
+
+    fn door_locked() -> ServiceError {
+        Self::DoorLocked.map_forbidden_error()
+    }
+
+    fn door_off_map(position: Vec3) -> ServiceError {
+        Self::DoorOffMap(position).map_validation_error()
+    }
+
+    fn door_taken(position: Vec3) -> ServiceError {
+        Self::DoorTaken(position).map_conflict_error()
+    }
+
+    fn door_not_found(position: Vec3) -> ServiceError {
+        Self::DoorNotFound(position).map_not_found_error()
+    }
+}
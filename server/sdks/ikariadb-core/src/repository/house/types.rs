@@ -0,0 +1,53 @@
+use crate::repository::{house::HouseV1, world::types::Rect};
+use spacetimedb::{SpacetimeType, Timestamp};
+use std::time::Duration;
+
+impl From<&HouseV1> for Rect {
+    fn from(house: &HouseV1) -> Self {
+        Self::new(house.x1, house.y1, house.x2, house.y2)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, SpacetimeType)]
+pub enum HouseRoleV1 {
+    Guest,
+    Subowner,
+    Owner,
+}
+
+impl HouseRoleV1 {
+    /// Owners manage subowners and guests, subowners only guests.
+    pub fn can_manage(&self, other: HouseRoleV1) -> bool {
+        *self > other
+    }
+}
+
+/// Paying early extends the current period; paying late starts a new one from `now`.
+pub fn next_paid_until(paid_until: Option<Timestamp>, now: Timestamp, period: Duration) -> Timestamp {
+    paid_until.filter(|paid_until| *paid_until > now).unwrap_or(now) + period
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roles_only_manage_lower_roles() {
+        assert!(HouseRoleV1::Owner.can_manage(HouseRoleV1::Subowner));
+        assert!(HouseRoleV1::Owner.can_manage(HouseRoleV1::Guest));
+        assert!(HouseRoleV1::Subowner.can_manage(HouseRoleV1::Guest));
+        assert!(!HouseRoleV1::Subowner.can_manage(HouseRoleV1::Subowner));
+        assert!(!HouseRoleV1::Subowner.can_manage(HouseRoleV1::Owner));
+        assert!(!HouseRoleV1::Guest.can_manage(HouseRoleV1::Guest));
+    }
+
+    #[test]
+    fn next_paid_until_extends_or_restarts_the_period() {
+        let now = Timestamp::from_micros_since_unix_epoch(1_000_000);
+        let period = Duration::from_secs(10);
+
+        assert_eq!(next_paid_until(None, now, period), now + period);
+        assert_eq!(next_paid_until(Some(now + period), now, period), now + period + period);
+        assert_eq!(next_paid_until(Some(Timestamp::UNIX_EPOCH), now, period), now + period);
+    }
+}
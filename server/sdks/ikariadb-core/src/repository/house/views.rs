@@ -0,0 +1,29 @@
+use crate::repository::{
+    character::online_character_v1__view,
+    house::{HouseAccessV1, HouseV1, house_access_v1__view, house_v1__view, types::HouseRoleV1},
+};
+use spacetimedb::{ViewContext, view};
+
+#[view(accessor = vw_house_all_v1, public)]
+pub fn vw_house_all_v1(ctx: &ViewContext) -> Vec<HouseV1> {
+    // Views cannot scan a whole table, so every house is listed through the floor index.
+    ctx.db.house_v1().z().filter(u8::MIN..=u8::MAX).collect()
+}
+
+/// Access lists of every house the current character belongs to; guests only see their own entry.
+#[view(accessor = vw_house_my_access_v1, public)]
+pub fn vw_house_my_access_v1(ctx: &ViewContext) -> Vec<HouseAccessV1> {
+    let Some(current) = ctx.db.online_character_v1().user_id().find(ctx.sender()) else {
+        return Vec::new();
+    };
+
+    let mut entries = Vec::new();
+    for access in ctx.db.house_access_v1().character_id().filter(current.character_id) {
+        if access.role == HouseRoleV1::Guest {
+            entries.push(access);
+        } else {
+            entries.extend(ctx.db.house_access_v1().house_id().filter(access.house_id));
+        }
+    }
+    entries
+}
@@ -1,16 +1,29 @@
-use crate::{error::ServiceResult, repository::event::services::EventReducerContext};
+use crate::{
+    config::WorldConfig,
+    error::ServiceResult,
+    repository::{
+        environment::services::EnvironmentReducerContext, event::services::EventReducerContext,
+        world::services::WorldReducerContext,
+    },
+};
 use spacetimedb::ReducerContext;
 
 pub mod character;
 pub mod chat;
+pub mod environment;
 pub mod event;
+pub mod house;
 pub mod item;
 pub mod progression;
 pub mod user;
 pub mod world;
 
-pub fn init(ctx: &ReducerContext) {
+pub fn init(ctx: &ReducerContext, config: WorldConfig) -> ServiceResult<()> {
+    ctx.world_services().configure(&config)?;
+    ctx.environment_services().start_world_clock();
+    ctx.environment_services().start_weather();
     ctx.publish().system_init();
+    Ok(())
 }
 
 pub fn identity_connected(ctx: &ReducerContext) -> ServiceResult<()> {
@@ -1,6 +1,8 @@
 use self::types::SkillV1;
-use spacetimedb::table;
+use spacetimedb::{Timestamp, table};
 
+pub mod reducers;
+pub mod services;
 pub mod types;
 
 #[table(accessor = character_skill_v1, private)]
@@ -14,3 +16,14 @@ pub struct CharacterSkillV1 {
     pub level: u16,
     pub progress_percent: u8,
 }
+
+#[table(accessor = completed_quest_v1, private)]
+pub struct CompletedQuestV1 {
+    #[auto_inc]
+    #[primary_key]
+    pub completed_quest_id: u64,
+    #[index(btree)]
+    pub character_id: u64,
+    pub quest_id: u64,
+    pub completed_at: Timestamp,
+}
@@ -0,0 +1,14 @@
+use crate::{
+    error::ServiceResult,
+    extend::validate::ReducerContextRequirements,
+    repository::{character::services::CharacterReducerContext, progression::services::ProgressionReducerContext},
+};
+use spacetimedb::{ReducerContext, reducer};
+
+#[reducer]
+pub fn complete_quest_v1(ctx: &ReducerContext, character_id: u64, quest_id: u64) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.character_services().get_offline(character_id)?;
+    ctx.progression_services().complete_quest(character_id, quest_id);
+    Ok(())
+}
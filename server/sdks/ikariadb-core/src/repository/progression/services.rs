@@ -0,0 +1,70 @@
+use crate::repository::progression::{CompletedQuestV1, character_skill_v1, completed_quest_v1};
+use spacetimedb::{ReducerContext, Table};
+use std::ops::Deref;
+
+pub trait ProgressionReducerContext {
+    fn progression_services(&self) -> ProgressionServices<'_>;
+}
+
+impl ProgressionReducerContext for ReducerContext {
+    fn progression_services(&self) -> ProgressionServices<'_> {
+        ProgressionServices { ctx: self }
+    }
+}
+
+pub struct ProgressionServices<'a> {
+    ctx: &'a ReducerContext,
+}
+
+impl Deref for ProgressionServices<'_> {
+    type Target = ReducerContext;
+
+    fn deref(&self) -> &Self::Target {
+        self.ctx
+    }
+}
+
+impl ProgressionServices<'_> {
+    pub fn has_completed_quest(&self, character_id: u64, quest_id: u64) -> bool {
+        self.db
+            .completed_quest_v1()
+            .character_id()
+            .filter(character_id)
+            .any(|quest| quest.quest_id == quest_id)
+    }
+
+    pub fn complete_quest(&self, character_id: u64, quest_id: u64) {
+        if self.has_completed_quest(character_id, quest_id) {
+            return;
+        }
+        self.db.completed_quest_v1().insert(CompletedQuestV1 {
+            completed_quest_id: 0,
+            character_id,
+            quest_id,
+            completed_at: self.timestamp,
+        });
+    }
+
+    pub fn forget_character(&self, character_id: u64) {
+        let skills: Vec<u64> = self
+            .db
+            .character_skill_v1()
+            .character_id()
+            .filter(character_id)
+            .map(|skill| skill.skill_entry_id)
+            .collect();
+        for skill_entry_id in skills {
+            self.db.character_skill_v1().skill_entry_id().delete(skill_entry_id);
+        }
+        let quests: Vec<u64> = self
+            .db
+            .completed_quest_v1()
+            .character_id()
+            .filter(character_id)
+            .map(|quest| quest.completed_quest_id)
+            .collect();
+        for completed_quest_id in quests {
+            self.db.completed_quest_v1().completed_quest_id().delete(completed_quest_id);
+        }
+    }
+}
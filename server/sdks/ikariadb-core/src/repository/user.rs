@@ -1,5 +1,6 @@
 use spacetimedb::{Identity, Timestamp, table};
 
+pub mod reducers;
 pub mod services;
 pub mod views;
 
@@ -10,3 +11,10 @@ pub struct UserV1 {
     pub created_at: Timestamp,
     pub last_active_at: Timestamp,
 }
+
+#[table(accessor = game_master_v1, private)]
+pub struct GameMasterV1 {
+    #[primary_key]
+    pub user_id: Identity,
+    pub granted_at: Timestamp,
+}
@@ -0,0 +1,11 @@
+use crate::{
+    error::ServiceResult, extend::validate::ReducerContextRequirements, repository::user::services::UserReducerContext,
+};
+use spacetimedb::{Identity, ReducerContext, reducer};
+
+#[reducer]
+pub fn grant_game_master_v1(ctx: &ReducerContext, user_id: Identity) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.user_services().grant_game_master(user_id);
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::repository::user::{UserV1, user_v1};
+use crate::repository::user::{GameMasterV1, UserV1, game_master_v1, user_v1};
 use spacetimedb::{Identity, ReducerContext};
 use std::ops::Deref;
 
@@ -42,6 +42,17 @@ impl UserServices<'_> {
         self.db.user_v1().user_id().insert_or_update(user);
     }
 
+    pub fn is_game_master(&self, user_id: Identity) -> bool {
+        self.db.game_master_v1().user_id().find(user_id).is_some()
+    }
+
+    pub fn grant_game_master(&self, user_id: Identity) {
+        self.db.game_master_v1().user_id().insert_or_update(GameMasterV1 {
+            user_id,
+            granted_at: self.timestamp,
+        });
+    }
+
     pub fn signed_out(&self, user_id: Identity) {
         if let Some(mut user) = self.db.user_v1().user_id().find(user_id) {
             user.last_active_at = self.timestamp;
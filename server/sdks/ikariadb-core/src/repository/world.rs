@@ -1,14 +1,42 @@
 use self::{
     services::WorldReducerContext,
-    types::{DirectionV1, MapTileV1},
+    types::{CornerRuleV1, DirectionV1, FloorTransitionV1, MapTileStackV1, PvpModeV1, RegionFlagsV1, WorldFeaturesV1},
+};
+use crate::{
+    error::ServiceResult,
+    extend::validate::ReducerContextRequirements,
+    repository::{character::types::StartingStatsV1, world::types::MovementV1},
 };
-use crate::{error::ServiceResult, extend::validate::ReducerContextRequirements, repository::world::types::MovementV1};
 use spacetimedb::{ReducerContext, ScheduleAt, Timestamp, reducer, table};
 
+pub mod minimap;
+pub mod pathfinding;
 pub mod reducers;
+pub mod sector_grid;
 pub mod services;
+pub mod terrain;
+pub mod tiled;
 pub mod types;
 pub mod views;
+pub mod visibility;
+
+/// Single-row table holding the settings the world binary passed to `init`.
+#[table(accessor = world_config_v1, private)]
+pub struct WorldConfigV1 {
+    #[primary_key]
+    pub world_config_id: u64,
+    pub spawn_x: u16,
+    pub spawn_y: u16,
+    pub spawn_z: u8,
+    pub pvp_mode: PvpModeV1,
+    pub features: WorldFeaturesV1,
+    pub corner_rule: CornerRuleV1,
+    pub max_characters_per_user: Option<u16>,
+    pub character_deletion_grace_days: u16,
+    pub character_rename_cooldown_days: u16,
+    pub character_name_reservation_days: u16,
+    pub starting_stats: StartingStatsV1,
+}
 
 #[table(accessor = map_v1, private)]
 pub struct MapV1 {
@@ -21,7 +49,59 @@ pub struct MapV1 {
     pub x2: u16,
     pub y2: u16,
     pub z: u8,
-    pub tile: MapTileV1,
+    pub tiles: MapTileStackV1,
+}
+
+/// Area carrying region flags, split along sector borders like `MapV1` rows. Regions may overlap, e.g. a house on top
+/// of a town's protection zone; the flags of a tile are the union of every region covering it.
+#[table(accessor = map_region_v1, private)]
+pub struct MapRegionV1 {
+    #[auto_inc]
+    #[primary_key]
+    pub map_region_id: u64,
+    #[index(btree)]
+    pub sector_key: u64,
+    pub x1: u16,
+    pub y1: u16,
+    pub x2: u16,
+    pub y2: u16,
+    pub z: u8,
+    pub flags: RegionFlagsV1,
+}
+
+/// Region flags at the tile an online character stands on.
+#[table(accessor = character_region_v1, private)]
+pub struct CharacterRegionV1 {
+    #[primary_key]
+    pub character_id: u64,
+    pub flags: RegionFlagsV1,
+}
+
+/// Where a stair, ladder or hole at `map_id` takes a character.
+#[table(accessor = floor_link_v1, private)]
+pub struct FloorLinkV1 {
+    #[primary_key]
+    pub map_id: u64,
+    #[index(btree)]
+    pub sector_key: u64,
+    pub transition: FloorTransitionV1,
+    pub target_x: u16,
+    pub target_y: u16,
+    pub target_z: u8,
+}
+
+/// Teleports whoever steps on `map_id` to the target, once the level and quest requirements are met.
+#[table(accessor = portal_v1, private)]
+pub struct PortalV1 {
+    #[primary_key]
+    pub map_id: u64,
+    #[index(btree)]
+    pub sector_key: u64,
+    pub target_x: u16,
+    pub target_y: u16,
+    pub target_z: u8,
+    pub min_level: Option<u16>,
+    pub required_quest_id: Option<u64>,
 }
 
 #[table(accessor = town_temple_v1, private)]
@@ -29,12 +109,26 @@ pub struct TownTempleV1 {
     #[auto_inc]
     #[primary_key]
     pub town_temple_id: u64,
+    #[unique]
     pub name: String,
+    #[unique]
+    pub map_id: u64,
     pub x: u16,
     pub y: u16,
+    #[index(btree)]
     pub z: u8,
 }
 
+/// Home temple of a character: where it respawns and which town it belongs to.
+#[table(accessor = citizenship_v1, private)]
+pub struct CitizenshipV1 {
+    #[primary_key]
+    pub character_id: u64,
+    #[index(btree)]
+    pub town_temple_id: u64,
+    pub joined_at: Timestamp,
+}
+
 #[table(accessor = movement_cooldown_v1, private)]
 pub struct MovementCooldownV1 {
     #[primary_key]
@@ -42,6 +136,22 @@ pub struct MovementCooldownV1 {
     pub can_move_at: Timestamp,
 }
 
+/// When a character may push someone again.
+#[table(accessor = push_cooldown_v1, private)]
+pub struct PushCooldownV1 {
+    #[primary_key]
+    pub character_id: u64,
+    pub can_push_at: Timestamp,
+}
+
+/// When a character may turn in place again.
+#[table(accessor = turn_cooldown_v1, private)]
+pub struct TurnCooldownV1 {
+    #[primary_key]
+    pub character_id: u64,
+    pub can_turn_at: Timestamp,
+}
+
 #[table(accessor = online_character_position_v1, private)]
 #[table(accessor = offline_character_position_v1, private)]
 pub struct CharacterPositionV1 {
@@ -55,9 +165,13 @@ pub struct CharacterPositionV1 {
     pub arrives_at: Timestamp,
 }
 
+/// Walkable map chunks a character stepped on recently, up to `WALKED_MAP_CHUNK_CACHE_SIZE` per character.
 #[table(accessor = walked_map_chunk_v1, private)]
 pub struct WalkedMapChunkV1 {
     #[primary_key]
+    #[auto_inc]
+    pub walked_map_chunk_id: u64,
+    #[index(btree)]
     pub character_id: u64,
     pub map_id: u64,
     pub x1: u16,
@@ -65,6 +179,20 @@ pub struct WalkedMapChunkV1 {
     pub x2: u16,
     pub y2: u16,
     pub z: u8,
+    pub step_time_percent: u16,
+    /// Last time the character walked into this chunk from another one; steps within the chunk leave it untouched.
+    pub last_walked_at: Timestamp,
+}
+
+/// Counters of the walked chunk cache, read with SQL to tune `WALKED_MAP_CHUNK_CACHE_SIZE`. Hits are not counted so
+/// cached lookups stay read-only; the hit rate is `1 - misses / steps` over the same period.
+#[table(accessor = walk_cache_stats_v1, private)]
+pub struct WalkCacheStatsV1 {
+    #[primary_key]
+    pub walk_cache_stats_id: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub invalidations: u64,
 }
 
 #[table(accessor = occupied_tile_v1, private)]
@@ -76,6 +204,31 @@ pub struct OccupiedTileV1 {
     pub character_ids: Vec<u64>,
 }
 
+/// Remaining steps of a `walk_to_v1` route, consumed one at a time by the movement intention scheduler.
+#[table(accessor = walk_path_v1, private)]
+pub struct WalkPathV1 {
+    #[primary_key]
+    pub character_id: u64,
+    pub goal_x: u16,
+    pub goal_y: u16,
+    pub goal_z: u8,
+    pub steps: Vec<MovementV1>,
+    pub replans: u8,
+}
+
+/// Minimap blocks a character has seen in one sector, kept across sessions.
+#[table(accessor = explored_sector_v1, private)]
+pub struct ExploredSectorV1 {
+    #[primary_key]
+    #[auto_inc]
+    pub explored_sector_id: u64,
+    #[index(btree)]
+    pub character_id: u64,
+    pub sector_key: u64,
+    /// One bit per `MINIMAP_BLOCK_SIZE` block of the sector, row-major.
+    pub blocks: Vec<u8>,
+}
+
 #[table(accessor = oneshot_movement_intention_v1, private, scheduled(oneshot_movement_intention_scheduled_v1))]
 pub struct OneshotMovementIntentionV1 {
     #[primary_key]
@@ -88,6 +241,6 @@ pub struct OneshotMovementIntentionV1 {
 pub fn oneshot_movement_intention_scheduled_v1(ctx: &ReducerContext, timer: OneshotMovementIntentionV1) -> ServiceResult<()> {
     ctx.require_internal_access()?;
     ctx.world_services()
-        .execute_movement_intention(timer.character_id, timer.movement);
+        .execute_movement_intention(timer.character_id, timer.movement)?;
     Ok(())
 }
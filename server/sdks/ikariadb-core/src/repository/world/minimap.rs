@@ -0,0 +1,94 @@
+use crate::{
+    constants::{MINIMAP_BLOCK_SIZE, SECTOR_SIZE},
+    repository::world::types::{Rect, Vec3},
+};
+
+pub const BLOCKS_PER_SECTOR_SIDE: u16 = SECTOR_SIZE / MINIMAP_BLOCK_SIZE;
+/// Size of the explored bitset of one sector, one bit per block in row-major order.
+pub const EXPLORED_BITSET_LEN: usize = (BLOCKS_PER_SECTOR_SIDE as usize * BLOCKS_PER_SECTOR_SIDE as usize).div_ceil(8);
+
+/// Minimap block a position belongs to; exploring only needs to happen when it changes.
+pub fn block_of(pos: Vec3) -> (u16, u16, u8) {
+    (pos.x / MINIMAP_BLOCK_SIZE, pos.y / MINIMAP_BLOCK_SIZE, pos.z)
+}
+
+/// Bits of every block touched by `rect` on floor `z`, grouped by sector key.
+pub fn explored_bits(rect: Rect, z: u8) -> Vec<(u64, Vec<usize>)> {
+    rect.split_by_sector()
+        .into_iter()
+        .map(|piece| {
+            let sector_key = Vec3::new(piece.min.x, piece.min.y, z).sector_key();
+            let mut bits = Vec::new();
+            for by in piece.min.y / MINIMAP_BLOCK_SIZE..=piece.max.y / MINIMAP_BLOCK_SIZE {
+                for bx in piece.min.x / MINIMAP_BLOCK_SIZE..=piece.max.x / MINIMAP_BLOCK_SIZE {
+                    let local_x = bx % BLOCKS_PER_SECTOR_SIDE;
+                    let local_y = by % BLOCKS_PER_SECTOR_SIDE;
+                    bits.push(local_y as usize * BLOCKS_PER_SECTOR_SIDE as usize + local_x as usize);
+                }
+            }
+            (sector_key, bits)
+        })
+        .collect()
+}
+
+/// Sets `bits` in `bitset`, growing it to full size if needed, and reports whether anything changed.
+pub fn mark_explored(bitset: &mut Vec<u8>, bits: &[usize]) -> bool {
+    if bitset.len() < EXPLORED_BITSET_LEN {
+        bitset.resize(EXPLORED_BITSET_LEN, 0);
+    }
+
+    let mut changed = false;
+    for &bit in bits {
+        let mask = 1 << (bit % 8);
+        if bitset[bit / 8] & mask == 0 {
+            bitset[bit / 8] |= mask;
+            changed = true;
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explored_bits_cover_every_touched_block() {
+        let groups = explored_bits(Rect::new(0, 0, 8, 7), 7);
+
+        assert_eq!(groups, vec![(Vec3::new(0, 0, 7).sector_key(), vec![0, 1])]);
+    }
+
+    #[test]
+    fn explored_bits_are_split_across_sectors() {
+        let groups = explored_bits(Rect::new(SECTOR_SIZE - 1, 0, SECTOR_SIZE, 0), 7);
+
+        assert_eq!(
+            groups,
+            vec![
+                (Vec3::new(0, 0, 7).sector_key(), vec![BLOCKS_PER_SECTOR_SIDE as usize - 1]),
+                (Vec3::new(SECTOR_SIZE, 0, 7).sector_key(), vec![0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn mark_explored_reports_only_new_bits() {
+        let mut bitset = Vec::new();
+
+        assert!(mark_explored(&mut bitset, &[0, 9]));
+        assert_eq!(bitset.len(), EXPLORED_BITSET_LEN);
+        assert_eq!(&bitset[..2], &[0b0000_0001, 0b0000_0010]);
+        assert!(!mark_explored(&mut bitset, &[9]));
+    }
+
+    #[test]
+    fn block_changes_every_block_size_tiles() {
+        assert_eq!(
+            block_of(Vec3::new(0, 0, 7)),
+            block_of(Vec3::new(MINIMAP_BLOCK_SIZE - 1, 0, 7))
+        );
+        assert_ne!(block_of(Vec3::new(0, 0, 7)), block_of(Vec3::new(MINIMAP_BLOCK_SIZE, 0, 7)));
+        assert_ne!(block_of(Vec3::new(0, 0, 7)), block_of(Vec3::new(0, 0, 8)));
+    }
+}
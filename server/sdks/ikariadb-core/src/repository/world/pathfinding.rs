@@ -0,0 +1,188 @@
+use crate::repository::world::types::{CornerRuleV1, MovementV1, Vec2, Vec3};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+/// 8-directional A* on a single floor. `is_passable` is asked about every tile except `start`; the search gives up
+/// once `node_budget` tiles have been expanded so a reducer cannot spend unbounded time on unreachable goals.
+/// Unless `corner_rule` is `Free`, diagonals also need both of their corners passable, so routes never rely on
+/// squeezing past anything `is_passable` rejects.
+pub fn find_path(
+    start: Vec3,
+    goal: Vec3,
+    node_budget: usize,
+    corner_rule: CornerRuleV1,
+    mut is_passable: impl FnMut(Vec3) -> bool,
+) -> Option<Vec<MovementV1>> {
+    if start.z != goal.z {
+        return None;
+    }
+    if start == goal {
+        return Some(Vec::new());
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<u64, (u64, MovementV1)> = HashMap::new();
+    let mut best_cost: HashMap<u64, u32> = HashMap::new();
+    let mut passable: HashMap<u64, bool> = HashMap::new();
+
+    best_cost.insert(start.map_id(), 0);
+    open.push(Reverse((heuristic(start, goal), 0, start.map_id())));
+
+    let mut expanded = 0;
+    while let Some(Reverse((_, cost, map_id))) = open.pop() {
+        if map_id == goal.map_id() {
+            return Some(rebuild_path(&came_from, start.map_id(), map_id));
+        }
+        if best_cost.get(&map_id).is_some_and(|&best| cost > best) {
+            continue;
+        }
+
+        expanded += 1;
+        if expanded > node_budget {
+            return None;
+        }
+
+        let current = Vec3::from_map_id(map_id);
+        for movement in MovementV1::ALL {
+            let (x, y) = movement.translate(current.x, current.y);
+            if x == current.x && y == current.y {
+                continue;
+            }
+
+            let next = Vec3::new(x, y, current.z);
+            let next_id = next.map_id();
+            let mut check = |pos: Vec2| {
+                let tile = pos.with_z(current.z);
+                *passable.entry(tile.map_id()).or_insert_with(|| is_passable(tile))
+            };
+            if !check(next.into()) || !corner_rule.allows(current.into(), movement, &mut check, |_| false) {
+                continue;
+            }
+
+            let next_cost = cost
+                + if movement.is_diagonal() {
+                    DIAGONAL_COST
+                } else {
+                    STRAIGHT_COST
+                };
+            if best_cost.get(&next_id).is_some_and(|&best| next_cost >= best) {
+                continue;
+            }
+            best_cost.insert(next_id, next_cost);
+            came_from.insert(next_id, (map_id, movement));
+            open.push(Reverse((next_cost + heuristic(next, goal), next_cost, next_id)));
+        }
+    }
+
+    None
+}
+
+/// Octile distance, admissible for straight and diagonal steps.
+fn heuristic(from: Vec3, to: Vec3) -> u32 {
+    let dx = from.x.abs_diff(to.x) as u32;
+    let dy = from.y.abs_diff(to.y) as u32;
+    STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+}
+
+fn rebuild_path(came_from: &HashMap<u64, (u64, MovementV1)>, start: u64, goal: u64) -> Vec<MovementV1> {
+    let mut steps = Vec::new();
+    let mut current = goal;
+    while current != start {
+        let (previous, movement) = came_from[&current];
+        steps.push(movement);
+        current = previous;
+    }
+    steps.reverse();
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walk(start: Vec3, steps: &[MovementV1]) -> Vec3 {
+        steps.iter().fold(start, |pos, movement| {
+            let (x, y) = movement.translate(pos.x, pos.y);
+            Vec3::new(x, y, pos.z)
+        })
+    }
+
+    #[test]
+    fn find_path_prefers_diagonals_on_open_ground() {
+        let start = Vec3::new(10, 10, 7);
+        let goal = Vec3::new(13, 15, 7);
+
+        let path = find_path(start, goal, 1000, CornerRuleV1::Free, |_| true).unwrap();
+
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.iter().filter(|movement| movement.is_diagonal()).count(), 3);
+        assert_eq!(walk(start, &path), goal);
+    }
+
+    #[test]
+    fn find_path_routes_around_a_wall() {
+        let start = Vec3::new(10, 10, 7);
+        let goal = Vec3::new(14, 10, 7);
+        let wall = |pos: Vec3| !(pos.x == 12 && (5..=12).contains(&pos.y));
+
+        let path = find_path(start, goal, 1000, CornerRuleV1::Free, wall).unwrap();
+
+        let mut pos = start;
+        for movement in &path {
+            pos = walk(pos, &[*movement]);
+            assert!(wall(pos));
+        }
+        assert_eq!(pos, goal);
+    }
+
+    #[test]
+    fn find_path_does_not_cut_corners_unless_free() {
+        let start = Vec3::new(10, 10, 7);
+        let goal = Vec3::new(11, 9, 7);
+        let pillar = |pos: Vec3| pos != Vec3::new(11, 10, 7);
+
+        assert_eq!(
+            find_path(start, goal, 100, CornerRuleV1::Free, pillar),
+            Some(vec![MovementV1::NorthEast])
+        );
+        assert_eq!(
+            find_path(start, goal, 100, CornerRuleV1::Terrain, pillar),
+            Some(vec![MovementV1::North, MovementV1::East])
+        );
+    }
+
+    #[test]
+    fn find_path_gives_up_on_unreachable_goal_within_budget() {
+        let start = Vec3::new(100, 100, 7);
+        let goal = Vec3::new(200, 100, 7);
+        let mut asked = 0;
+
+        let path = find_path(start, goal, 64, CornerRuleV1::Free, |pos| {
+            asked += 1;
+            pos.x != 150
+        });
+
+        assert_eq!(path, None);
+        assert!(asked <= 64 * 8);
+    }
+
+    #[test]
+    fn find_path_rejects_other_floors_and_blocked_goals() {
+        let start = Vec3::new(10, 10, 7);
+
+        assert_eq!(
+            find_path(start, Vec3::new(10, 10, 8), 100, CornerRuleV1::Free, |_| true),
+            None
+        );
+        assert_eq!(
+            find_path(start, Vec3::new(11, 10, 7), 100, CornerRuleV1::Free, |pos| pos.x != 11),
+            None
+        );
+        assert_eq!(find_path(start, start, 100, CornerRuleV1::Free, |_| false), Some(Vec::new()));
+    }
+}
@@ -1,7 +1,10 @@
 use crate::{
     error::ServiceResult,
     extend::validate::ReducerContextRequirements,
-    repository::world::{services::WorldReducerContext, types::MovementV1},
+    repository::world::{
+        services::WorldReducerContext,
+        types::{DirectionV1, MapTileStackV1, MovementV1, Rect, RegionFlagsV1, Vec3},
+    },
 };
 use spacetimedb::{ReducerContext, reducer};
 
@@ -11,3 +14,158 @@ pub fn move_character_v1(ctx: &ReducerContext, movement: MovementV1) -> ServiceR
     ctx.world_services().move_character(character.character_id, movement)?;
     Ok(())
 }
+
+#[reducer]
+pub fn turn_character_v1(ctx: &ReducerContext, direction: DirectionV1) -> ServiceResult<()> {
+    let character = ctx.require_online()?;
+    ctx.world_services().turn_character(character.character_id, direction)?;
+    Ok(())
+}
+
+#[reducer]
+pub fn push_character_v1(ctx: &ReducerContext, target_character_id: u64, movement: MovementV1) -> ServiceResult<()> {
+    let character = ctx.require_online()?;
+    ctx.world_services()
+        .push_character(character.character_id, target_character_id, movement)?;
+    Ok(())
+}
+
+#[reducer]
+pub fn walk_to_v1(ctx: &ReducerContext, x: u16, y: u16, z: u8) -> ServiceResult<()> {
+    let character = ctx.require_online()?;
+    ctx.world_services().walk_to(character.character_id, Vec3::new(x, y, z))?;
+    Ok(())
+}
+
+#[reducer]
+pub fn import_map_v1(ctx: &ReducerContext, tiled_json: String) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.world_services().import_map(&tiled_json)?;
+    Ok(())
+}
+
+#[reducer]
+pub fn compact_map_v1(ctx: &ReducerContext) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.world_services().compact_map();
+    Ok(())
+}
+
+#[reducer]
+pub fn reset_walk_cache_stats_v1(ctx: &ReducerContext) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.world_services().reset_walk_cache_stats();
+    Ok(())
+}
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+pub fn paint_map_rect_v1(
+    ctx: &ReducerContext,
+    x1: u16,
+    y1: u16,
+    x2: u16,
+    y2: u16,
+    z: u8,
+    tiles: MapTileStackV1,
+) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.world_services().paint_map_rect(Rect::new(x1, y1, x2, y2), z, tiles)?;
+    Ok(())
+}
+
+#[reducer]
+pub fn erase_map_rect_v1(ctx: &ReducerContext, x1: u16, y1: u16, x2: u16, y2: u16, z: u8) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.world_services().erase_map_rect(Rect::new(x1, y1, x2, y2), z)?;
+    Ok(())
+}
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+pub fn copy_map_region_v1(
+    ctx: &ReducerContext,
+    x1: u16,
+    y1: u16,
+    x2: u16,
+    y2: u16,
+    z: u8,
+    target_x: u16,
+    target_y: u16,
+    target_z: u8,
+) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.world_services()
+        .copy_map_region(Rect::new(x1, y1, x2, y2), z, Vec3::new(target_x, target_y, target_z))?;
+    Ok(())
+}
+
+#[reducer]
+pub fn use_stair_v1(ctx: &ReducerContext, x: u16, y: u16, z: u8) -> ServiceResult<()> {
+    let character = ctx.require_online()?;
+    ctx.world_services().use_stair(character.character_id, Vec3::new(x, y, z))?;
+    Ok(())
+}
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+pub fn link_floor_v1(
+    ctx: &ReducerContext,
+    x: u16,
+    y: u16,
+    z: u8,
+    target_x: u16,
+    target_y: u16,
+    target_z: u8,
+) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.world_services()
+        .link_floor(Vec3::new(x, y, z), Vec3::new(target_x, target_y, target_z))?;
+    Ok(())
+}
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+pub fn create_portal_v1(
+    ctx: &ReducerContext,
+    x: u16,
+    y: u16,
+    z: u8,
+    target_x: u16,
+    target_y: u16,
+    target_z: u8,
+    min_level: Option<u16>,
+    required_quest_id: Option<u64>,
+) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.world_services().create_portal(
+        Vec3::new(x, y, z),
+        Vec3::new(target_x, target_y, target_z),
+        min_level,
+        required_quest_id,
+    )?;
+    Ok(())
+}
+
+#[reducer]
+pub fn remove_portal_v1(ctx: &ReducerContext, x: u16, y: u16, z: u8) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.world_services().remove_portal(Vec3::new(x, y, z))?;
+    Ok(())
+}
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+pub fn set_region_flags_v1(
+    ctx: &ReducerContext,
+    x1: u16,
+    y1: u16,
+    x2: u16,
+    y2: u16,
+    z: u8,
+    flags: RegionFlagsV1,
+) -> ServiceResult<()> {
+    ctx.require_game_master()?;
+    ctx.world_services().set_region_flags(Rect::new(x1, y1, x2, y2), z, flags)?;
+    Ok(())
+}
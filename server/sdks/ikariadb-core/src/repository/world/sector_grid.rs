@@ -0,0 +1,221 @@
+use crate::{
+    constants::SECTOR_SIZE,
+    repository::world::{
+        FloorLinkV1, MapRegionV1, MapV1, OccupiedTileV1, PortalV1,
+        types::{Rect, Vec2, Vec3},
+    },
+};
+use std::ops::RangeInclusive;
+
+/// Row of a table indexed by `sector_key`, described by the area it covers on its floor.
+pub trait SectorIndexed {
+    fn sector_area(&self) -> (Rect, u8);
+}
+
+impl<T: SectorIndexed> SectorIndexed for &T {
+    fn sector_area(&self) -> (Rect, u8) {
+        (*self).sector_area()
+    }
+}
+
+impl SectorIndexed for MapV1 {
+    fn sector_area(&self) -> (Rect, u8) {
+        (Rect::from(self), self.z)
+    }
+}
+
+impl SectorIndexed for MapRegionV1 {
+    fn sector_area(&self) -> (Rect, u8) {
+        (Rect::from(self), self.z)
+    }
+}
+
+impl SectorIndexed for OccupiedTileV1 {
+    fn sector_area(&self) -> (Rect, u8) {
+        tile_area(self.map_id)
+    }
+}
+
+impl SectorIndexed for FloorLinkV1 {
+    fn sector_area(&self) -> (Rect, u8) {
+        tile_area(self.map_id)
+    }
+}
+
+impl SectorIndexed for PortalV1 {
+    fn sector_area(&self) -> (Rect, u8) {
+        tile_area(self.map_id)
+    }
+}
+
+fn tile_area(map_id: u64) -> (Rect, u8) {
+    let pos = Vec3::from_map_id(map_id);
+    (Rect::new(pos.x, pos.y, pos.x, pos.y), pos.z)
+}
+
+/// Spatial query over tables indexed by `sector_key`: a rect, optionally with a hole, across a range of floors.
+/// It resolves the sectors to look up and drops the rows those sectors return outside of the queried area.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectorGrid {
+    rect: Rect,
+    hole: Option<Rect>,
+    floors: RangeInclusive<u8>,
+}
+
+impl SectorGrid {
+    pub fn rect(rect: Rect, floors: RangeInclusive<u8>) -> Self {
+        Self {
+            rect,
+            hole: None,
+            floors,
+        }
+    }
+
+    /// Square of `radius` tiles around `center`, on the given floors.
+    pub fn radius(center: Vec2, radius: u16, floors: RangeInclusive<u8>) -> Self {
+        Self::rect(Rect::around(center, radius), floors)
+    }
+
+    /// Tiles further than `inner` but no further than `outer` from `center`, e.g. what just came into view.
+    pub fn ring(center: Vec2, inner: u16, outer: u16, floors: RangeInclusive<u8>) -> Self {
+        Self {
+            rect: Rect::around(center, outer),
+            hole: (inner < outer).then(|| Rect::around(center, inner)),
+            floors,
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn floors(&self) -> RangeInclusive<u8> {
+        self.floors.clone()
+    }
+
+    /// Sectors touched by the query, floor by floor; sectors lying entirely in the hole are skipped.
+    pub fn sector_keys(&self) -> impl Iterator<Item = u64> + '_ {
+        let sectors_x = self.rect.min.x / SECTOR_SIZE..=self.rect.max.x / SECTOR_SIZE;
+        let sectors_y = self.rect.min.y / SECTOR_SIZE..=self.rect.max.y / SECTOR_SIZE;
+        self.floors().flat_map(move |z| {
+            let sectors_y = sectors_y.clone();
+            sectors_x.clone().flat_map(move |sx| {
+                sectors_y.clone().filter_map(move |sy| {
+                    let origin = Vec2::new(sx * SECTOR_SIZE, sy * SECTOR_SIZE);
+                    let sector = Rect::new(
+                        origin.x,
+                        origin.y,
+                        origin.x.saturating_add(SECTOR_SIZE - 1),
+                        origin.y.saturating_add(SECTOR_SIZE - 1),
+                    );
+                    (!self.hides(sector)).then(|| origin.with_z(z).sector_key())
+                })
+            })
+        })
+    }
+
+    pub fn contains(&self, pos: Vec3) -> bool {
+        self.overlaps(Rect::new(pos.x, pos.y, pos.x, pos.y), pos.z)
+    }
+
+    /// Whether any tile of `rect` on floor `z` is part of the query.
+    pub fn overlaps(&self, rect: Rect, z: u8) -> bool {
+        self.floors.contains(&z) && self.rect.overlaps(&rect) && !self.hides(rect)
+    }
+
+    /// Runs `lookup` for every sector of the query and keeps the rows overlapping it, e.g.
+    /// `grid.query(|sector_key| ctx.db.map_v1().sector_key().filter(sector_key))`.
+    pub fn query<T, I, F>(&self, lookup: F) -> impl Iterator<Item = T>
+    where
+        T: SectorIndexed,
+        I: IntoIterator<Item = T>,
+        F: FnMut(u64) -> I,
+    {
+        self.sector_keys().flat_map(lookup).filter(|row| {
+            let (rect, z) = row.sector_area();
+            self.overlaps(rect, z)
+        })
+    }
+
+    fn hides(&self, rect: Rect) -> bool {
+        self.hole
+            .is_some_and(|hole| hole.contains(rect.min) && hole.contains(rect.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn occupied(pos: Vec3) -> OccupiedTileV1 {
+        OccupiedTileV1 {
+            map_id: pos.map_id(),
+            sector_key: pos.sector_key(),
+            character_ids: vec![pos.map_id()],
+        }
+    }
+
+    fn index(rows: Vec<OccupiedTileV1>) -> BTreeMap<u64, Vec<OccupiedTileV1>> {
+        let mut index: BTreeMap<u64, Vec<OccupiedTileV1>> = BTreeMap::new();
+        for row in rows {
+            index.entry(row.sector_key).or_default().push(row);
+        }
+        index
+    }
+
+    fn query_positions(grid: &SectorGrid, index: &BTreeMap<u64, Vec<OccupiedTileV1>>) -> Vec<Vec3> {
+        grid.query(|sector_key| index.get(&sector_key).into_iter().flatten())
+            .map(|row| Vec3::from_map_id(row.map_id))
+            .collect()
+    }
+
+    #[test]
+    fn sector_keys_cover_every_floor_and_touched_sector() {
+        let grid = SectorGrid::radius(Vec2::new(SECTOR_SIZE, 10), 4, 6..=7);
+
+        let keys: Vec<_> = grid.sector_keys().collect();
+
+        assert_eq!(
+            keys,
+            vec![
+                Vec3::new(0, 0, 6).sector_key(),
+                Vec3::new(SECTOR_SIZE, 0, 6).sector_key(),
+                Vec3::new(0, 0, 7).sector_key(),
+                Vec3::new(SECTOR_SIZE, 0, 7).sector_key(),
+            ]
+        );
+    }
+
+    #[test]
+    fn query_drops_rows_outside_the_area_and_floors() {
+        let index = index(vec![
+            occupied(Vec3::new(100, 100, 7)),
+            occupied(Vec3::new(105, 100, 7)),
+            occupied(Vec3::new(100, 100, 8)),
+            occupied(Vec3::new(200, 200, 7)),
+        ]);
+
+        let found = query_positions(&SectorGrid::radius(Vec2::new(100, 100), 5, 7..=7), &index);
+
+        assert_eq!(found, vec![Vec3::new(100, 100, 7), Vec3::new(105, 100, 7)]);
+    }
+
+    #[test]
+    fn ring_excludes_the_inner_square_and_skips_sectors_inside_it() {
+        let index = index(vec![
+            occupied(Vec3::new(1000, 1000, 7)),
+            occupied(Vec3::new(1003, 1000, 7)),
+            occupied(Vec3::new(1004, 1000, 7)),
+        ]);
+        let grid = SectorGrid::ring(Vec2::new(1000, 1000), 3, 4, 7..=7);
+
+        assert_eq!(query_positions(&grid, &index), vec![Vec3::new(1004, 1000, 7)]);
+        assert!(grid.overlaps(Rect::new(990, 1000, 1000, 1000), 7));
+        assert!(!grid.overlaps(Rect::new(998, 998, 1002, 1002), 7));
+
+        let wide = SectorGrid::ring(Vec2::new(640, 640), 400, 410, 7..=7);
+        assert!(!wide.sector_keys().any(|key| key == Vec3::new(512, 512, 7).sector_key()));
+        assert!(wide.sector_keys().any(|key| key == Vec3::new(0, 0, 7).sector_key()));
+    }
+}
@@ -1,23 +1,44 @@
 use crate::{
+    config::{MapSource, WORLD_CONFIG_ID, WorldConfig},
     constants::{
-        DEFAULT_CHARACTER_SPEED, DEFAULT_SPAWN_X, DEFAULT_SPAWN_Y, MOVEMENT_COOLDOWN_FACTOR, MOVEMENT_INTENTION_WINDOW_MS,
-        SECTOR_SIZE,
+        HOUSE_EXIT_RADIUS, MINIMAP_EXPLORE_RADIUS, MOVEMENT_INTENTION_WINDOW_MS, PATHFINDING_NODE_BUDGET,
+        PORTAL_ARRIVAL_RADIUS, PUSH_COOLDOWN_MS, TEMPLE_SPAWN_RADIUS, TURN_COOLDOWN_MS, WALK_CACHE_STATS_ID, WALK_MAX_REPLANS,
+        WALKED_MAP_CHUNK_CACHE_SIZE,
     },
     error::{ErrorMapper, ServiceError, ServiceResult},
     repository::{
         character::{character_v1, services::CharacterReducerContext},
+        environment::services::EnvironmentReducerContext,
+        house::services::HouseReducerContext,
+        progression::services::ProgressionReducerContext,
         world::{
-            CharacterPositionV1, MapV1, MovementCooldownV1, OccupiedTileV1, OneshotMovementIntentionV1, WalkedMapChunkV1,
-            map_v1, movement_cooldown_v1, occupied_tile_v1, offline_character_position_v1, oneshot_movement_intention_v1,
-            online_character_position_v1,
-            types::{DirectionV1, MapTileV1, MovementV1, Rect, Vec2, Vec3},
-            walked_map_chunk_v1,
+            CharacterPositionV1, CharacterRegionV1, CitizenshipV1, ExploredSectorV1, FloorLinkV1, MapRegionV1, MapV1,
+            MovementCooldownV1, OccupiedTileV1, OneshotMovementIntentionV1, PortalV1, PushCooldownV1, TownTempleV1,
+            TurnCooldownV1, WalkCacheStatsV1, WalkPathV1, WalkedMapChunkV1, WorldConfigV1, character_region_v1, citizenship_v1,
+            explored_sector_v1, floor_link_v1, map_region_v1, map_v1, minimap, movement_cooldown_v1, occupied_tile_v1,
+            offline_character_position_v1, oneshot_movement_intention_v1, online_character_position_v1,
+            pathfinding::find_path,
+            portal_v1, push_cooldown_v1,
+            sector_grid::SectorGrid,
+            tiled::parse_tiled_map,
+            town_temple_v1, turn_cooldown_v1,
+            types::{
+                DirectionV1, FloorTransitionV1, MapBlueprint, MapCompactionReport, MapTileStackV1, MovementV1, Rect,
+                RegionFlagsV1, StepOutcome, TempleSeed, TileGrid, Vec2, Vec3, floor_links_in, region_rows_in,
+                stale_walked_chunks, stranded_characters, walked_chunks_to_evict,
+            },
+            walk_cache_stats_v1, walk_path_v1, walked_map_chunk_v1, world_config_v1,
         },
     },
 };
 use ikaria_shared::constants::GROUND_LEVEL;
+use log::info;
 use spacetimedb::{Identity, ReducerContext, Table, Timestamp};
-use std::{ops::Deref, time::Duration};
+use std::{
+    collections::{BTreeSet, HashMap},
+    ops::Deref,
+    time::Duration,
+};
 use thiserror::Error;
 
 pub trait WorldReducerContext {
@@ -43,6 +64,26 @@ impl Deref for WorldServices<'_> {
 }
 
 impl WorldServices<'_> {
+    /// Stores the world settings and seeds the map on first publish.
+    pub fn configure(&self, config: &WorldConfig) -> ServiceResult<()> {
+        let mut row = WorldConfigV1::from(config);
+        if let Some(spawn) = self.seed_initial_map(config.map_source)? {
+            row.spawn_x = spawn.x;
+            row.spawn_y = spawn.y;
+            row.spawn_z = spawn.z;
+        }
+        self.db.world_config_v1().world_config_id().insert_or_update(row);
+        Ok(())
+    }
+
+    pub fn config(&self) -> WorldConfigV1 {
+        self.db
+            .world_config_v1()
+            .world_config_id()
+            .find(WORLD_CONFIG_ID)
+            .unwrap_or_else(|| WorldConfigV1::from(&WorldConfig::default()))
+    }
+
     pub fn find_online_position(&self, character_id: u64) -> Option<CharacterPositionV1> {
         self.db.online_character_position_v1().character_id().find(character_id)
     }
@@ -72,36 +113,99 @@ impl WorldServices<'_> {
             .find(|chunk| chunk.z == pos.z && Rect::from(chunk).contains(point))
     }
 
-    pub fn is_walkable(&self, position: &CharacterPositionV1, target: Vec3) -> bool {
-        if let Some(cache) = self.db.walked_map_chunk_v1().character_id().find(position.character_id)
-            && cache.z == target.z
-            && Rect::from(&cache).contains(target.into())
-        {
-            return true;
+    /// Step time percentage of `target` when the character can walk onto it, `None` otherwise. Steps within a cached
+    /// chunk read it without writing; walking into another cached chunk only stamps when it was last used.
+    pub fn walkable_step_time(&self, position: &CharacterPositionV1, target: Vec3) -> Option<u16> {
+        let cached = self
+            .db
+            .walked_map_chunk_v1()
+            .character_id()
+            .filter(position.character_id)
+            .find(|cache| cache.z == target.z && Rect::from(cache).contains(target.into()));
+        if let Some(mut cache) = cached {
+            let step_time_percent = cache.step_time_percent;
+            let entered = cache.z != position.z || !Rect::from(&cache).contains(Vec2::new(position.x, position.y));
+            if entered {
+                cache.last_walked_at = self.timestamp;
+                self.db.walked_map_chunk_v1().walked_map_chunk_id().update(cache);
+            }
+            return Some(step_time_percent);
         }
 
-        let Some(chunk) = self.find_map_at(target) else {
-            return false;
-        };
+        let chunk = self.find_map_at(target)?;
+        if !chunk.tiles.is_walkable() {
+            return None;
+        }
+
+        self.record_walk_cache(|stats| stats.misses += 1);
+        self.remember_walked_chunk(position.character_id, &chunk);
+        Some(chunk.tiles.step_time_percent())
+    }
 
-        if !chunk.tile.is_walkable() {
-            return false;
+    /// Caches a walkable chunk for the character, dropping the least recently walked ones when the cache is full.
+    fn remember_walked_chunk(&self, character_id: u64, chunk: &MapV1) {
+        let cached: Vec<WalkedMapChunkV1> = self.db.walked_map_chunk_v1().character_id().filter(character_id).collect();
+        if cached.iter().any(|cache| cache.map_id == chunk.map_id) {
+            return;
         }
 
-        self.db
+        let evicted = walked_chunks_to_evict(&cached, WALKED_MAP_CHUNK_CACHE_SIZE);
+        if !evicted.is_empty() {
+            let evictions = evicted.len() as u64;
+            self.drop_walked_chunks(evicted);
+            self.record_walk_cache(|stats| stats.evictions += evictions);
+        }
+
+        self.db.walked_map_chunk_v1().insert(WalkedMapChunkV1 {
+            walked_map_chunk_id: 0,
+            character_id,
+            map_id: chunk.map_id,
+            x1: chunk.x1,
+            y1: chunk.y1,
+            x2: chunk.x2,
+            y2: chunk.y2,
+            z: chunk.z,
+            step_time_percent: chunk.tiles.step_time_percent(),
+            last_walked_at: self.timestamp,
+        });
+    }
+
+    fn forget_walked_chunks(&self, character_id: u64) {
+        let cached = self
+            .db
             .walked_map_chunk_v1()
             .character_id()
-            .insert_or_update(WalkedMapChunkV1 {
-                character_id: position.character_id,
-                map_id: chunk.map_id,
-                x1: chunk.x1,
-                y1: chunk.y1,
-                x2: chunk.x2,
-                y2: chunk.y2,
-                z: chunk.z,
-            });
+            .filter(character_id)
+            .map(|cache| cache.walked_map_chunk_id)
+            .collect();
+        self.drop_walked_chunks(cached);
+    }
 
-        true
+    fn drop_walked_chunks(&self, walked_map_chunk_ids: Vec<u64>) {
+        for walked_map_chunk_id in walked_map_chunk_ids {
+            self.db
+                .walked_map_chunk_v1()
+                .walked_map_chunk_id()
+                .delete(walked_map_chunk_id);
+        }
+    }
+
+    fn record_walk_cache(&self, update: impl FnOnce(&mut WalkCacheStatsV1)) {
+        let mut stats = self
+            .db
+            .walk_cache_stats_v1()
+            .walk_cache_stats_id()
+            .find(WALK_CACHE_STATS_ID)
+            .unwrap_or_else(|| WalkCacheStatsV1::new(WALK_CACHE_STATS_ID));
+        update(&mut stats);
+        self.db.walk_cache_stats_v1().walk_cache_stats_id().insert_or_update(stats);
+    }
+
+    pub fn reset_walk_cache_stats(&self) {
+        self.db
+            .walk_cache_stats_v1()
+            .walk_cache_stats_id()
+            .insert_or_update(WalkCacheStatsV1::new(WALK_CACHE_STATS_ID));
     }
 
     pub fn is_occupied(&self, pos: Vec3) -> bool {
@@ -156,8 +260,12 @@ impl WorldServices<'_> {
         let position = self
             .find_offline_position(character_id)
             .or_else(|| self.find_online_position(character_id))
+            .filter(|position| {
+                let pos = Vec3::new(position.x, position.y, position.z);
+                self.is_standable(pos) && self.house_services().can_stay_at(character_id, pos)
+            })
             .unwrap_or_else(|| {
-                let spawn = Vec3::new(DEFAULT_SPAWN_X, DEFAULT_SPAWN_Y, GROUND_LEVEL);
+                let spawn = self.respawn_position(character_id);
                 CharacterPositionV1 {
                     character_id,
                     x: spawn.x,
@@ -169,109 +277,726 @@ impl WorldServices<'_> {
                 }
             });
 
-        let map_id = Vec3::new(position.x, position.y, position.z).map_id();
+        let pos = Vec3::new(position.x, position.y, position.z);
         self.db.offline_character_position_v1().character_id().delete(character_id);
-        self.occupy_tile(map_id, character_id);
+        self.occupy_tile(pos.map_id(), character_id);
+        self.refresh_region_awareness(character_id, pos);
+        self.explore_around(character_id, pos);
         self.db
             .online_character_position_v1()
             .character_id()
             .insert_or_update(position);
     }
 
+    /// Sends an online character back to its home temple, e.g. after dying.
+    pub fn respawn_character(&self, character_id: u64) -> ServiceResult<()> {
+        self.cancel_walk(character_id);
+        let position = self.get_online_position(character_id)?;
+        let destination = self.respawn_position(character_id);
+        let movement = position.movement;
+        let direction = position.direction;
+        self.place_character(position, destination, movement, direction, self.timestamp);
+        Ok(())
+    }
+
+    /// Moves an online character to a free tile outside `area` near `exit`, or home when there is none.
+    pub fn move_character_out(&self, character_id: u64, area: Rect, exit: Option<Vec3>) -> ServiceResult<()> {
+        let destination =
+            exit.and_then(|exit| self.find_free_tile_near_where(exit, HOUSE_EXIT_RADIUS, |pos| !area.contains(pos.into())));
+        let Some(destination) = destination else {
+            return self.respawn_character(character_id);
+        };
+
+        self.cancel_walk(character_id);
+        let position = self.get_online_position(character_id)?;
+        let movement = position.movement;
+        let direction = position.direction;
+        self.place_character(position, destination, movement, direction, self.timestamp);
+        Ok(())
+    }
+
+    /// Free tile around the character's home temple, falling back to the configured spawn when no temple exists.
+    fn respawn_position(&self, character_id: u64) -> Vec3 {
+        let config = self.config();
+        let spawn = Vec3::new(config.spawn_x, config.spawn_y, config.spawn_z);
+        let Some(temple) = self
+            .find_home_temple(character_id)
+            .or_else(|| self.adopt_nearest_temple(character_id, spawn))
+        else {
+            return spawn;
+        };
+
+        let temple = Vec3::new(temple.x, temple.y, temple.z);
+        self.find_free_tile_near(temple, TEMPLE_SPAWN_RADIUS).unwrap_or(temple)
+    }
+
+    fn find_free_tile_near(&self, center: Vec3, radius: u16) -> Option<Vec3> {
+        self.find_free_tile_near_where(center, radius, |_| true)
+    }
+
+    fn find_free_tile_near_where(&self, center: Vec3, radius: u16, accept: impl Fn(Vec3) -> bool) -> Option<Vec3> {
+        for ring in 0..=radius {
+            for x in center.x.saturating_sub(ring)..=center.x.saturating_add(ring) {
+                for y in center.y.saturating_sub(ring)..=center.y.saturating_add(ring) {
+                    if x.abs_diff(center.x) != ring && y.abs_diff(center.y) != ring {
+                        continue;
+                    }
+                    let pos = Vec3::new(x, y, center.z);
+                    if accept(pos) && self.is_standable(pos) && !self.is_occupied(pos) {
+                        return Some(pos);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn is_standable(&self, pos: Vec3) -> bool {
+        self.find_map_at(pos).is_some_and(|chunk| chunk.tiles.is_walkable())
+    }
+
+    pub fn find_home_temple(&self, character_id: u64) -> Option<TownTempleV1> {
+        let citizenship = self.db.citizenship_v1().character_id().find(character_id)?;
+        self.db.town_temple_v1().town_temple_id().find(citizenship.town_temple_id)
+    }
+
+    fn adopt_nearest_temple(&self, character_id: u64, near: Vec3) -> Option<TownTempleV1> {
+        let temple = self.db.town_temple_v1().iter().min_by_key(|temple| {
+            (
+                temple.z.abs_diff(near.z),
+                temple.x.abs_diff(near.x).max(temple.y.abs_diff(near.y)),
+                temple.town_temple_id,
+            )
+        })?;
+        self.set_citizenship(character_id, temple.town_temple_id);
+        Some(temple)
+    }
+
+    fn set_citizenship(&self, character_id: u64, town_temple_id: u64) {
+        self.db.citizenship_v1().character_id().insert_or_update(CitizenshipV1 {
+            character_id,
+            town_temple_id,
+            joined_at: self.timestamp,
+        });
+    }
+
+    /// Stepping onto a temple tile makes the character a citizen of that town.
+    fn claim_citizenship_at(&self, character_id: u64, pos: Vec3) {
+        let Some(temple) = self.db.town_temple_v1().map_id().find(pos.map_id()) else {
+            return;
+        };
+        let current = self.db.citizenship_v1().character_id().find(character_id);
+        if current.is_none_or(|citizenship| citizenship.town_temple_id != temple.town_temple_id) {
+            self.set_citizenship(character_id, temple.town_temple_id);
+        }
+    }
+
+    /// Matches temples by name so citizenships survive a map re-import; temples missing from the map are removed.
+    fn sync_temples(&self, seeds: &[TempleSeed]) {
+        let removed: Vec<u64> = self
+            .db
+            .town_temple_v1()
+            .iter()
+            .filter(|temple| !seeds.iter().any(|seed| seed.name == temple.name))
+            .map(|temple| temple.town_temple_id)
+            .collect();
+        for town_temple_id in removed {
+            self.remove_temple(town_temple_id);
+        }
+
+        for seed in seeds {
+            let pos = seed.position;
+            let temple = TownTempleV1 {
+                town_temple_id: 0,
+                name: seed.name.clone(),
+                map_id: pos.map_id(),
+                x: pos.x,
+                y: pos.y,
+                z: pos.z,
+            };
+            if let Some(existing) = self.db.town_temple_v1().name().find(&seed.name) {
+                self.db.town_temple_v1().town_temple_id().update(TownTempleV1 {
+                    town_temple_id: existing.town_temple_id,
+                    ..temple
+                });
+            } else {
+                self.db.town_temple_v1().insert(temple);
+            }
+        }
+    }
+
+    /// Removes a temple; its citizens adopt the nearest remaining temple the next time they respawn.
+    fn remove_temple(&self, town_temple_id: u64) {
+        let citizens: Vec<u64> = self
+            .db
+            .citizenship_v1()
+            .town_temple_id()
+            .filter(town_temple_id)
+            .map(|citizenship| citizenship.character_id)
+            .collect();
+        for character_id in citizens {
+            self.db.citizenship_v1().character_id().delete(character_id);
+        }
+        self.db.town_temple_v1().town_temple_id().delete(town_temple_id);
+    }
+
     pub fn despawn_character(&self, user_id: Identity) {
         for character in self.db.character_v1().user_id().filter(user_id) {
-            let character_id = character.character_id;
-            if let Some(position) = self.find_online_position(character_id) {
-                let map_id = Vec3::new(position.x, position.y, position.z).map_id();
-                self.vacate_tile(map_id, character_id);
-                self.db
-                    .offline_character_position_v1()
-                    .character_id()
-                    .insert_or_update(position);
-            }
-            self.db.online_character_position_v1().character_id().delete(character_id);
-            self.db.movement_cooldown_v1().character_id().delete(character_id);
-            self.db.oneshot_movement_intention_v1().character_id().delete(character_id);
-            self.db.walked_map_chunk_v1().character_id().delete(character_id);
+            self.take_offline(character.character_id);
         }
     }
 
-    pub fn seed_initial_map(&self) {
+    /// Removes every world row of a deleted character, including what it keeps while offline.
+    pub fn forget_character(&self, character_id: u64) {
+        self.take_offline(character_id);
+        self.db.offline_character_position_v1().character_id().delete(character_id);
+        self.db.citizenship_v1().character_id().delete(character_id);
+        let explored: Vec<u64> = self
+            .db
+            .explored_sector_v1()
+            .character_id()
+            .filter(character_id)
+            .map(|explored| explored.explored_sector_id)
+            .collect();
+        for explored_sector_id in explored {
+            self.db.explored_sector_v1().explored_sector_id().delete(explored_sector_id);
+        }
+    }
+
+    fn take_offline(&self, character_id: u64) {
+        if let Some(position) = self.find_online_position(character_id) {
+            let map_id = Vec3::new(position.x, position.y, position.z).map_id();
+            self.vacate_tile(map_id, character_id);
+            self.db
+                .offline_character_position_v1()
+                .character_id()
+                .insert_or_update(position);
+        }
+        self.db.online_character_position_v1().character_id().delete(character_id);
+        self.db.movement_cooldown_v1().character_id().delete(character_id);
+        self.db.push_cooldown_v1().character_id().delete(character_id);
+        self.db.turn_cooldown_v1().character_id().delete(character_id);
+        self.db.oneshot_movement_intention_v1().character_id().delete(character_id);
+        self.db.walk_path_v1().character_id().delete(character_id);
+        self.db.character_region_v1().character_id().delete(character_id);
+        self.forget_walked_chunks(character_id);
+    }
+
+    /// Seeds an empty map and returns the spawn picked by the generator, if the source chose one.
+    pub fn seed_initial_map(&self, source: MapSource) -> ServiceResult<Option<Vec3>> {
         let existing_count = self.db.map_v1().count();
         if existing_count > 0 {
-            return;
+            return Ok(None);
         }
 
-        let grass_start: u16 = 1024;
-        let grass_end: u16 = grass_start + 256;
-        let water_margin: u16 = 16;
-        let edge_start = grass_start - water_margin;
-        let edge_end = grass_end + water_margin;
+        let (blueprint, spawn) = source.blueprint()?;
+        if let (MapSource::Procedural(settings), Some(spawn)) = (source, spawn) {
+            info!(
+                "Terrain generated: seed={}, spawn=({}, {}, {})",
+                settings.seed, spawn.x, spawn.y, spawn.z
+            );
+        }
+        self.apply_map_blueprint(&blueprint);
+        Ok(spawn)
+    }
 
-        // Grass area
-        self.insert_rect_chunks(
-            Rect::new(grass_start, grass_start, grass_end, grass_end),
-            GROUND_LEVEL,
-            MapTileV1::Grass,
-        );
+    /// Replaces the whole map with a Tiled JSON export. Portals and doors left without ground are dropped and online
+    /// characters standing in the void are sent home.
+    pub fn import_map(&self, source: &str) -> ServiceResult<()> {
+        let blueprint = parse_tiled_map(source)?;
+        self.clear_map();
+        self.apply_map_blueprint(&blueprint);
+        self.drop_detached_portals();
+        self.house_services().drop_doors_off_map();
+        self.relocate_stranded_characters()
+    }
 
-        // Water margins: top
-        self.insert_rect_chunks(
-            Rect::new(edge_start, edge_start, edge_end, grass_start - 1),
-            GROUND_LEVEL,
-            MapTileV1::Water,
-        );
-        // Water margins: bottom
-        self.insert_rect_chunks(
-            Rect::new(edge_start, grass_end + 1, edge_end, edge_end),
-            GROUND_LEVEL,
-            MapTileV1::Water,
-        );
-        // Water margins: left
-        self.insert_rect_chunks(
-            Rect::new(edge_start, grass_start, grass_start - 1, grass_end),
-            GROUND_LEVEL,
-            MapTileV1::Water,
+    fn drop_detached_portals(&self) {
+        let detached: Vec<u64> = self
+            .db
+            .portal_v1()
+            .iter()
+            .filter(|portal| {
+                let target = Vec3::new(portal.target_x, portal.target_y, portal.target_z);
+                !self.is_standable(Vec3::from_map_id(portal.map_id)) || !self.is_standable(target)
+            })
+            .map(|portal| portal.map_id)
+            .collect();
+        for map_id in detached {
+            self.db.portal_v1().map_id().delete(map_id);
+        }
+    }
+
+    fn relocate_stranded_characters(&self) -> ServiceResult<()> {
+        let positions: Vec<CharacterPositionV1> = self.db.online_character_position_v1().iter().collect();
+        for character_id in stranded_characters(&positions, |pos| self.is_standable(pos)) {
+            self.respawn_character(character_id)?;
+        }
+        Ok(())
+    }
+
+    fn clear_map(&self) {
+        let chunks: Vec<u64> = self.db.map_v1().iter().map(|chunk| chunk.map_id).collect();
+        for map_id in chunks {
+            self.db.map_v1().map_id().delete(map_id);
+        }
+        let links: Vec<u64> = self.db.floor_link_v1().iter().map(|link| link.map_id).collect();
+        for map_id in links {
+            self.db.floor_link_v1().map_id().delete(map_id);
+        }
+        let regions: Vec<u64> = self.db.map_region_v1().iter().map(|region| region.map_region_id).collect();
+        for map_region_id in regions {
+            self.db.map_region_v1().map_region_id().delete(map_region_id);
+        }
+        let cached = self
+            .db
+            .walked_map_chunk_v1()
+            .iter()
+            .map(|cache| cache.walked_map_chunk_id)
+            .collect();
+        self.drop_walked_chunks(cached);
+    }
+
+    fn apply_map_blueprint(&self, blueprint: &MapBlueprint) {
+        let rows = blueprint.map_rows();
+        let row_count = rows.len();
+        for row in rows {
+            if row.z == GROUND_LEVEL {
+                self.environment_services().track_weather_at(Vec3::new(row.x1, row.y1, row.z));
+            }
+            self.db.map_v1().insert(row);
+        }
+        for link in blueprint.floor_links() {
+            self.db.floor_link_v1().map_id().insert_or_update(link);
+        }
+        for region in blueprint.region_rows() {
+            self.db.map_region_v1().insert(region);
+        }
+        self.sync_temples(&blueprint.temples);
+        info!(
+            "Map seeded: blueprint_chunks={}, rows={}, temples={}, regions={}",
+            blueprint.chunks.len(),
+            row_count,
+            blueprint.temples.len(),
+            blueprint.regions.len()
         );
-        // Water margins: right
-        self.insert_rect_chunks(
-            Rect::new(grass_end + 1, grass_start, edge_end, grass_end),
-            GROUND_LEVEL,
-            MapTileV1::Water,
+    }
+
+    /// Greedily merges adjacent rows holding the same tiles within each sector.
+    pub fn compact_map(&self) -> MapCompactionReport {
+        let sector_keys: BTreeSet<u64> = self.db.map_v1().iter().map(|chunk| chunk.sector_key).collect();
+
+        let mut report = MapCompactionReport::default();
+        for sector_key in sector_keys {
+            let sector = self.compact_sector(sector_key);
+            report.rows_before += sector.rows_before;
+            report.rows_after += sector.rows_after;
+        }
+
+        info!(
+            "Map compacted: rows_before={}, rows_after={}",
+            report.rows_before, report.rows_after
         );
+        report
     }
 
-    fn insert_rect_chunks(&self, rect: Rect, z: u8, tile: MapTileV1) {
-        if rect.min.x > rect.max.x || rect.min.y > rect.max.y {
-            return;
+    fn compact_sector(&self, sector_key: u64) -> MapCompactionReport {
+        let rows: Vec<MapV1> = self.db.map_v1().sector_key().filter(sector_key).collect();
+        let rows_before = rows.len() as u64;
+
+        let z_levels: BTreeSet<u8> = rows.iter().map(|row| row.z).collect();
+        let mut merged = Vec::new();
+        for z in z_levels {
+            let rects: Vec<_> = rows
+                .iter()
+                .filter(|row| row.z == z)
+                .map(|row| (Rect::from(row), row.tiles.clone()))
+                .collect();
+            if let Some(grid) = TileGrid::from_rects(&rects) {
+                merged.extend(grid.merge_rects().into_iter().map(|(rect, tiles)| (rect, z, tiles)));
+            }
         }
 
-        let mut cx = rect.min.x;
-        while cx <= rect.max.x {
-            let sector_end_x = ((cx / SECTOR_SIZE) + 1) * SECTOR_SIZE - 1;
-            let chunk_x2 = sector_end_x.min(rect.max.x);
-            let mut cy = rect.min.y;
-            while cy <= rect.max.y {
-                let sector_end_y = ((cy / SECTOR_SIZE) + 1) * SECTOR_SIZE - 1;
-                let chunk_y2 = sector_end_y.min(rect.max.y);
-                let pos = Vec3::new(cx, cy, z);
-                self.db.map_v1().insert(MapV1 {
-                    map_id: pos.map_id(),
-                    sector_key: pos.sector_key(),
-                    x1: cx,
-                    y1: cy,
-                    x2: chunk_x2,
-                    y2: chunk_y2,
-                    z,
-                    tile,
-                });
-                cy = chunk_y2 + 1;
+        let rows_after = merged.len() as u64;
+        if rows_after >= rows_before {
+            return MapCompactionReport {
+                rows_before,
+                rows_after: rows_before,
+            };
+        }
+
+        for row in rows {
+            self.db.map_v1().map_id().delete(row.map_id);
+        }
+        for (rect, z, tiles) in merged {
+            self.insert_rect_chunks(rect, z, &tiles);
+        }
+
+        MapCompactionReport { rows_before, rows_after }
+    }
+
+    pub fn paint_map_rect(&self, rect: Rect, z: u8, tiles: MapTileStackV1) -> ServiceResult<()> {
+        if !rect.is_valid() {
+            return Err(WorldError::invalid_rect());
+        }
+        if !tiles.ground.is_ground() {
+            return Err(WorldError::not_ground_tile());
+        }
+
+        self.carve_map_rect(rect, z);
+        self.insert_rect_chunks(rect, z, &tiles);
+        self.seed_floor_links(rect, z, &tiles);
+        self.finish_map_edit(rect, z)
+    }
+
+    pub fn erase_map_rect(&self, rect: Rect, z: u8) -> ServiceResult<()> {
+        if !rect.is_valid() {
+            return Err(WorldError::invalid_rect());
+        }
+
+        self.carve_map_rect(rect, z);
+        self.finish_map_edit(rect, z)
+    }
+
+    /// Copies every map row inside `source` at `z` so that its top-left corner lands on `target`.
+    /// The target area is cleared first, so empty cells in the source are pasted as empty.
+    pub fn copy_map_region(&self, source: Rect, z: u8, target: Vec3) -> ServiceResult<()> {
+        if !source.is_valid() {
+            return Err(WorldError::invalid_rect());
+        }
+
+        let width = source.max.x - source.min.x;
+        let height = source.max.y - source.min.y;
+        let (Some(max_x), Some(max_y)) = (target.x.checked_add(width), target.y.checked_add(height)) else {
+            return Err(WorldError::invalid_rect());
+        };
+        let destination = Rect::new(target.x, target.y, max_x, max_y);
+
+        let pieces: Vec<_> = self
+            .find_map_rows_in(source, z)
+            .into_iter()
+            .filter_map(|row| Some((Rect::from(&row).intersection(&source)?, row.tiles)))
+            .collect();
+
+        self.carve_map_rect(destination, target.z);
+        for (piece, tiles) in pieces {
+            let moved = Rect::new(
+                target.x + (piece.min.x - source.min.x),
+                target.y + (piece.min.y - source.min.y),
+                target.x + (piece.max.x - source.min.x),
+                target.y + (piece.max.y - source.min.y),
+            );
+            self.insert_rect_chunks(moved, target.z, &tiles);
+            self.seed_floor_links(moved, target.z, &tiles);
+        }
+        self.finish_map_edit(destination, target.z)
+    }
+
+    fn find_map_rows_in(&self, rect: Rect, z: u8) -> Vec<MapV1> {
+        SectorGrid::rect(rect, z..=z)
+            .query(|sector_key| self.db.map_v1().sector_key().filter(sector_key))
+            .collect()
+    }
+
+    /// Removes `rect` from the map, splitting every overlapping row into the pieces outside of it.
+    fn carve_map_rect(&self, rect: Rect, z: u8) {
+        for row in self.find_map_rows_in(rect, z) {
+            self.db.map_v1().map_id().delete(row.map_id);
+            for piece in Rect::from(&row).subtract(&rect) {
+                self.insert_rect_chunks(piece, z, &row.tiles);
+            }
+        }
+        self.clear_floor_links(rect, z);
+        self.clear_map_features(rect, z);
+    }
+
+    pub fn find_floor_link(&self, pos: Vec3) -> Option<FloorLinkV1> {
+        self.db.floor_link_v1().map_id().find(pos.map_id())
+    }
+
+    /// Links every cell of `rect` holding a stair, ladder or hole to the same position on the adjacent floor.
+    fn seed_floor_links(&self, rect: Rect, z: u8, tiles: &MapTileStackV1) {
+        for link in floor_links_in(rect, z, tiles) {
+            self.db.floor_link_v1().map_id().insert_or_update(link);
+        }
+    }
+
+    fn clear_floor_links(&self, rect: Rect, z: u8) {
+        let links: Vec<FloorLinkV1> = SectorGrid::rect(rect, z..=z)
+            .query(|sector_key| self.db.floor_link_v1().sector_key().filter(sector_key))
+            .collect();
+        for link in links {
+            self.db.floor_link_v1().map_id().delete(link.map_id);
+        }
+    }
+
+    /// Removes portals leading from or to `rect`, temples and house doors inside it, since their tiles are replaced.
+    fn clear_map_features(&self, rect: Rect, z: u8) {
+        let portals: Vec<u64> = self
+            .db
+            .portal_v1()
+            .iter()
+            .filter(|portal| {
+                let source = Vec3::from_map_id(portal.map_id);
+                let target = Vec3::new(portal.target_x, portal.target_y, portal.target_z);
+                [source, target].iter().any(|pos| pos.z == z && rect.contains((*pos).into()))
+            })
+            .map(|portal| portal.map_id)
+            .collect();
+        for map_id in portals {
+            self.db.portal_v1().map_id().delete(map_id);
+        }
+
+        let temples: Vec<u64> = self
+            .db
+            .town_temple_v1()
+            .z()
+            .filter(z)
+            .filter(|temple| rect.contains(Vec2::new(temple.x, temple.y)))
+            .map(|temple| temple.town_temple_id)
+            .collect();
+        for town_temple_id in temples {
+            self.remove_temple(town_temple_id);
+        }
+
+        self.house_services().remove_doors_in(rect, z);
+    }
+
+    /// Points an existing stair, ladder or hole at a custom destination.
+    pub fn link_floor(&self, source: Vec3, target: Vec3) -> ServiceResult<()> {
+        let Some(transition) = self.find_map_at(source).and_then(|chunk| chunk.tiles.floor_transition()) else {
+            return Err(WorldError::floor_link_not_found(source));
+        };
+
+        self.db.floor_link_v1().map_id().insert_or_update(FloorLinkV1 {
+            map_id: source.map_id(),
+            sector_key: source.sector_key(),
+            transition,
+            target_x: target.x,
+            target_y: target.y,
+            target_z: target.z,
+        });
+        Ok(())
+    }
+
+    pub fn use_stair(&self, character_id: u64, stair: Vec3) -> ServiceResult<()> {
+        self.cancel_walk(character_id);
+        if !self.is_movement_allowed(character_id) {
+            return Err(WorldError::movement_on_cooldown());
+        }
+
+        let character = self.character_services().get_online(character_id)?;
+        let position = self.get_online_position(character.character_id)?;
+        if !Vec3::new(position.x, position.y, position.z).is_adjacent_to(stair) {
+            return Err(WorldError::stair_out_of_reach());
+        }
+
+        let destination = match self.find_floor_link(stair) {
+            Some(link) if link.transition == FloorTransitionV1::Up => Vec3::new(link.target_x, link.target_y, link.target_z),
+            _ => return Err(WorldError::floor_link_not_found(stair)),
+        };
+        let step_time_percent = self.ensure_can_enter(&position, destination)?;
+
+        // Climbing takes one straight step, whichever way the character last moved.
+        let arrives_at =
+            self.compute_movement_arrives_at(character.character_id, MovementV1::default(), destination, step_time_percent);
+        let movement = position.movement;
+        let direction = position.direction;
+        self.arrive(position, destination, movement, direction, arrives_at)?;
+        Ok(())
+    }
+
+    /// Whether a diagonal step from `position` cuts a corner the world's corner rule forbids. Corners are probed
+    /// without going through the walked chunk cache, since the character never stands on them.
+    fn cuts_corner(&self, position: &CharacterPositionV1, movement: MovementV1) -> bool {
+        !self.config().corner_rule.allows(
+            Vec2::new(position.x, position.y),
+            movement,
+            |corner| self.is_standable(corner.with_z(position.z)),
+            |corner| self.is_occupied(corner.with_z(position.z)),
+        )
+    }
+
+    /// Checks the character can step onto `target` and returns the step time percentage of its ground.
+    fn ensure_can_enter(&self, position: &CharacterPositionV1, target: Vec3) -> ServiceResult<u16> {
+        if self.is_occupied(target) {
+            return Err(WorldError::tile_occupied());
+        }
+        self.walkable_step_time(position, target)
+            .ok_or_else(WorldError::tile_not_walkable)
+    }
+
+    /// Compacts the edited sectors and sends home the characters left without ground or walled in.
+    fn finish_map_edit(&self, rect: Rect, z: u8) -> ServiceResult<()> {
+        for sector_key in SectorGrid::rect(rect, z..=z).sector_keys() {
+            self.compact_sector(sector_key);
+        }
+        self.invalidate_walked_map_chunks(rect, z);
+        self.relocate_stranded_characters()
+    }
+
+    fn invalidate_walked_map_chunks(&self, rect: Rect, z: u8) {
+        let cached: Vec<WalkedMapChunkV1> = self.db.walked_map_chunk_v1().iter().collect();
+        let stale = stale_walked_chunks(&cached, rect, z);
+        if !stale.is_empty() {
+            let invalidations = stale.len() as u64;
+            self.drop_walked_chunks(stale);
+            self.record_walk_cache(|stats| stats.invalidations += invalidations);
+        }
+    }
+
+    fn insert_rect_chunks(&self, rect: Rect, z: u8, tiles: &MapTileStackV1) {
+        for piece in rect.split_by_sector() {
+            let pos = piece.min.with_z(z);
+            if z == GROUND_LEVEL {
+                self.environment_services().track_weather_at(pos);
+            }
+            self.db.map_v1().insert(MapV1 {
+                map_id: pos.map_id(),
+                sector_key: pos.sector_key(),
+                x1: piece.min.x,
+                y1: piece.min.y,
+                x2: piece.max.x,
+                y2: piece.max.y,
+                z,
+                tiles: tiles.clone(),
+            });
+        }
+    }
+
+    /// Flags of every region covering `pos`, with `no_pvp` decided by the world's PvP mode.
+    pub fn region_flags_at(&self, pos: Vec3) -> RegionFlagsV1 {
+        let point = Vec2::from(pos);
+        let flags = self
+            .db
+            .map_region_v1()
+            .sector_key()
+            .filter(pos.sector_key())
+            .filter(|region| region.z == pos.z && Rect::from(region).contains(point))
+            .fold(RegionFlagsV1::default(), |flags, region| flags.union(region.flags));
+        self.config().pvp_mode.apply(flags)
+    }
+
+    /// Replaces the region flags inside `rect`; empty flags clear the area. The `house` flag belongs to the houses
+    /// there and is kept.
+    pub fn set_region_flags(&self, rect: Rect, z: u8, flags: RegionFlagsV1) -> ServiceResult<()> {
+        if !rect.is_valid() {
+            return Err(WorldError::invalid_rect());
+        }
+
+        let regions: Vec<MapRegionV1> = SectorGrid::rect(rect, z..=z)
+            .query(|sector_key| self.db.map_region_v1().sector_key().filter(sector_key))
+            .collect();
+        for region in regions {
+            self.db.map_region_v1().map_region_id().delete(region.map_region_id);
+            let area = Rect::from(&region);
+            for piece in area.subtract(&rect) {
+                self.insert_region(piece, z, region.flags);
+            }
+            if region.flags.house
+                && let Some(inside) = area.intersection(&rect)
+            {
+                let house = RegionFlagsV1 {
+                    house: true,
+                    ..RegionFlagsV1::default()
+                };
+                self.insert_region(inside, z, house);
             }
-            cx = chunk_x2 + 1;
         }
+        if !flags.is_empty() {
+            self.insert_region(rect, z, flags);
+        }
+
+        self.refresh_region_awareness_in(rect, z);
+        Ok(())
+    }
+
+    /// Adds `flags` on top of whatever regions already cover `rect`.
+    pub fn add_region_flags(&self, rect: Rect, z: u8, flags: RegionFlagsV1) -> ServiceResult<()> {
+        if !rect.is_valid() {
+            return Err(WorldError::invalid_rect());
+        }
+
+        self.insert_region(rect, z, flags);
+        self.refresh_region_awareness_in(rect, z);
+        Ok(())
+    }
+
+    /// Clears only `flags` inside `rect`, leaving the other flags of the regions there in place.
+    pub fn remove_region_flags(&self, rect: Rect, z: u8, flags: RegionFlagsV1) -> ServiceResult<()> {
+        if !rect.is_valid() {
+            return Err(WorldError::invalid_rect());
+        }
+
+        let regions: Vec<MapRegionV1> = SectorGrid::rect(rect, z..=z)
+            .query(|sector_key| self.db.map_region_v1().sector_key().filter(sector_key))
+            .filter(|region| region.flags.difference(flags) != region.flags)
+            .collect();
+        for region in regions {
+            self.db.map_region_v1().map_region_id().delete(region.map_region_id);
+            let area = Rect::from(&region);
+            for piece in area.subtract(&rect) {
+                self.insert_region(piece, z, region.flags);
+            }
+            let remaining = region.flags.difference(flags);
+            if let Some(inside) = area.intersection(&rect)
+                && !remaining.is_empty()
+            {
+                self.insert_region(inside, z, remaining);
+            }
+        }
+
+        self.refresh_region_awareness_in(rect, z);
+        Ok(())
+    }
+
+    fn refresh_region_awareness_in(&self, rect: Rect, z: u8) {
+        let positions: Vec<(u64, Vec3)> = self
+            .db
+            .online_character_position_v1()
+            .iter()
+            .map(|position| (position.character_id, Vec3::new(position.x, position.y, position.z)))
+            .filter(|(_, pos)| pos.z == z && rect.contains((*pos).into()))
+            .collect();
+        for (character_id, pos) in positions {
+            self.refresh_region_awareness(character_id, pos);
+        }
+    }
+
+    fn insert_region(&self, rect: Rect, z: u8, flags: RegionFlagsV1) {
+        for region in region_rows_in(rect, z, flags) {
+            self.db.map_region_v1().insert(region);
+        }
+    }
+
+    /// Keeps the character's region row in step with where it stands, writing only when the flags change.
+    fn refresh_region_awareness(&self, character_id: u64, pos: Vec3) {
+        let flags = self.region_flags_at(pos);
+        let current = self.db.character_region_v1().character_id().find(character_id);
+        if current.is_some_and(|region| region.flags == flags) {
+            return;
+        }
+        self.db
+            .character_region_v1()
+            .character_id()
+            .insert_or_update(CharacterRegionV1 { character_id, flags });
+    }
+
+    pub fn ensure_can_log_out(&self, character_id: u64) -> ServiceResult<()> {
+        let Some(position) = self.find_online_position(character_id) else {
+            return Ok(());
+        };
+        if !self
+            .region_flags_at(Vec3::new(position.x, position.y, position.z))
+            .allows_logout()
+        {
+            return Err(WorldError::logout_blocked());
+        }
+        Ok(())
     }
 
     pub fn move_character(&self, character_id: u64, movement: MovementV1) -> ServiceResult<()> {
+        self.cancel_walk(character_id);
         if let Some(cooldown) = self.find_cooldown(character_id)
             && self.timestamp < cooldown.can_move_at
         {
@@ -284,17 +1009,217 @@ impl WorldServices<'_> {
             return Err(WorldError::movement_on_cooldown());
         }
 
-        self.execute_movement(character_id, movement)
+        self.step_or_turn(character_id, movement)
+    }
+
+    /// Steps towards `movement`; a blocked step still turns the character to face where it tried to go.
+    fn step_or_turn(&self, character_id: u64, movement: MovementV1) -> ServiceResult<()> {
+        if self.execute_movement(character_id, movement)? == StepOutcome::Blocked {
+            self.face(character_id, movement.into())?;
+        }
+        Ok(())
+    }
+
+    pub fn turn_character(&self, character_id: u64, direction: DirectionV1) -> ServiceResult<()> {
+        if let Some(cooldown) = self.db.turn_cooldown_v1().character_id().find(character_id)
+            && self.timestamp < cooldown.can_turn_at
+        {
+            return Err(WorldError::turn_on_cooldown());
+        }
+
+        self.cancel_walk(character_id);
+        self.face(character_id, direction)?;
+        self.db.turn_cooldown_v1().character_id().insert_or_update(TurnCooldownV1 {
+            character_id,
+            can_turn_at: self.timestamp + Duration::from_millis(TURN_COOLDOWN_MS),
+        });
+        Ok(())
+    }
+
+    fn face(&self, character_id: u64, direction: DirectionV1) -> ServiceResult<()> {
+        let mut position = self.get_online_position(character_id)?;
+        if position.direction != direction {
+            position.direction = direction;
+            self.db.online_character_position_v1().character_id().update(position);
+        }
+        Ok(())
+    }
+
+    /// Shoves an adjacent character one tile towards `movement`, e.g. out of a corridor it is blocking. The pushed
+    /// character pays the step like a regular move and cannot be pushed off its floor or out of a protection zone.
+    pub fn push_character(&self, character_id: u64, target_character_id: u64, movement: MovementV1) -> ServiceResult<()> {
+        if character_id == target_character_id {
+            return Err(WorldError::cannot_push_self());
+        }
+        if let Some(cooldown) = self.db.push_cooldown_v1().character_id().find(character_id)
+            && self.timestamp < cooldown.can_push_at
+        {
+            return Err(WorldError::push_on_cooldown());
+        }
+
+        let pusher = self.get_online_position(character_id)?;
+        let position = self.get_online_position(target_character_id)?;
+        let origin = Vec3::new(position.x, position.y, position.z);
+        if !Vec3::new(pusher.x, pusher.y, pusher.z).is_adjacent_to(origin) {
+            return Err(WorldError::push_out_of_reach());
+        }
+
+        let (target_x, target_y) = movement.translate(position.x, position.y);
+        if target_x == position.x && target_y == position.y {
+            return Err(WorldError::movement_out_of_bounds());
+        }
+        let destination = Vec3::new(target_x, target_y, position.z);
+        let leaves_floor = self.find_portal(destination).is_some()
+            || self
+                .find_floor_link(destination)
+                .is_some_and(|link| link.transition == FloorTransitionV1::Down);
+        if leaves_floor {
+            return Err(WorldError::push_leaves_floor());
+        }
+        if self.region_flags_at(origin).protection_zone && !self.region_flags_at(destination).protection_zone {
+            return Err(WorldError::push_out_of_protection_zone());
+        }
+        if self.cuts_corner(&position, movement) {
+            return Err(WorldError::corner_blocked());
+        }
+        let step_time_percent = self.ensure_can_enter(&position, destination)?;
+        self.house_services().ensure_can_pass_door(target_character_id, destination)?;
+
+        self.cancel_walk(target_character_id);
+        let arrives_at = self.compute_movement_arrives_at(target_character_id, movement, destination, step_time_percent);
+        let direction = position.direction;
+        self.place_character(position, destination, movement, direction, arrives_at);
+        self.db.push_cooldown_v1().character_id().insert_or_update(PushCooldownV1 {
+            character_id,
+            can_push_at: self.timestamp + Duration::from_millis(PUSH_COOLDOWN_MS),
+        });
+        Ok(())
     }
 
-    pub fn execute_movement_intention(&self, character_id: u64, movement: MovementV1) {
+    pub fn execute_movement_intention(&self, character_id: u64, movement: MovementV1) -> ServiceResult<()> {
+        if let Some(walk) = self.db.walk_path_v1().character_id().find(character_id) {
+            self.advance_walk(walk);
+            return Ok(());
+        }
+
+        if !self.is_movement_allowed(character_id) {
+            return Ok(());
+        }
+        self.step_or_turn(character_id, movement)
+    }
+
+    /// Plans a route to `goal` and queues its first step; later steps are queued as each one lands.
+    pub fn walk_to(&self, character_id: u64, goal: Vec3) -> ServiceResult<()> {
+        self.cancel_walk(character_id);
+        let position = self.get_online_position(character_id)?;
+        let steps = self
+            .plan_path(&position, goal)
+            .ok_or_else(|| WorldError::path_not_found(goal))?;
+
+        let Some(&first) = steps.first() else {
+            return Ok(());
+        };
+        self.db.walk_path_v1().insert(WalkPathV1 {
+            character_id,
+            goal_x: goal.x,
+            goal_y: goal.y,
+            goal_z: goal.z,
+            steps,
+            replans: 0,
+        });
+        self.schedule_movement_intention(character_id, first, self.next_move_at(character_id));
+        Ok(())
+    }
+
+    pub fn cancel_walk(&self, character_id: u64) {
+        if self.db.walk_path_v1().character_id().delete(character_id) {
+            self.db.oneshot_movement_intention_v1().character_id().delete(character_id);
+        }
+    }
+
+    fn advance_walk(&self, mut walk: WalkPathV1) {
+        let character_id = walk.character_id;
         if !self.is_movement_allowed(character_id) {
+            if let Some(&step) = walk.steps.first() {
+                self.schedule_movement_intention(character_id, step, self.next_move_at(character_id));
+            }
+            return;
+        }
+
+        let Some(&step) = walk.steps.first() else {
+            self.cancel_walk(character_id);
             return;
+        };
+
+        let expected = self.find_online_position(character_id).map(|position| {
+            let (x, y) = step.translate(position.x, position.y);
+            Vec3::new(x, y, position.z)
+        });
+        if let Ok(StepOutcome::Moved(landed)) = self.execute_movement(character_id, step) {
+            // A hole or portal along the way leaves the rest of the route meaningless.
+            if Some(landed) != expected {
+                self.cancel_walk(character_id);
+                return;
+            }
+            walk.steps.remove(0);
+        } else {
+            let goal = Vec3::new(walk.goal_x, walk.goal_y, walk.goal_z);
+            let replanned = self
+                .find_online_position(character_id)
+                .filter(|_| walk.replans < WALK_MAX_REPLANS)
+                .and_then(|position| self.plan_path(&position, goal));
+            let Some(steps) = replanned else {
+                self.cancel_walk(character_id);
+                return;
+            };
+            walk.steps = steps;
+            walk.replans += 1;
         }
-        let _ = self.execute_movement(character_id, movement);
+
+        let Some(&next) = walk.steps.first() else {
+            self.cancel_walk(character_id);
+            return;
+        };
+        self.db.walk_path_v1().character_id().update(walk);
+        self.schedule_movement_intention(character_id, next, self.next_move_at(character_id));
+    }
+
+    /// Routes over walkable, unoccupied tiles of the current floor, steering clear of holes and portals unless the goal
+    /// is one.
+    fn plan_path(&self, position: &CharacterPositionV1, goal: Vec3) -> Option<Vec<MovementV1>> {
+        let start = Vec3::new(position.x, position.y, position.z);
+        let mut sectors: HashMap<u64, Vec<MapV1>> = HashMap::new();
+        let is_passable = |pos: Vec3| {
+            let rows = sectors
+                .entry(pos.sector_key())
+                .or_insert_with(|| self.db.map_v1().sector_key().filter(pos.sector_key()).collect());
+            let walkable = rows
+                .iter()
+                .find(|chunk| chunk.z == pos.z && Rect::from(*chunk).contains(pos.into()))
+                .is_some_and(|chunk| chunk.tiles.is_walkable());
+            let leaves_floor = pos != goal
+                && (self.find_portal(pos).is_some()
+                    || self
+                        .find_floor_link(pos)
+                        .is_some_and(|link| link.transition == FloorTransitionV1::Down));
+            walkable
+                && !leaves_floor
+                && !self.is_occupied(pos)
+                && self.house_services().can_pass_door(position.character_id, pos)
+        };
+        find_path(start, goal, PATHFINDING_NODE_BUDGET, self.config().corner_rule, is_passable)
+    }
+
+    fn next_move_at(&self, character_id: u64) -> Timestamp {
+        self.find_cooldown(character_id)
+            .map(|cooldown| cooldown.can_move_at.max(self.timestamp))
+            .unwrap_or(self.timestamp)
     }
 
-    fn execute_movement(&self, character_id: u64, movement: MovementV1) -> ServiceResult<()> {
+    /// Moves the character one step and returns where it ended up, which differs from the step target when it fell
+    /// through a hole or entered a portal. Terrain, occupants and corners in the way block the step without failing;
+    /// anything else is an error.
+    fn execute_movement(&self, character_id: u64, movement: MovementV1) -> ServiceResult<StepOutcome> {
         let character = self.character_services().get_online(character_id)?;
         let position = self.get_online_position(character.character_id)?;
 
@@ -304,35 +1229,175 @@ impl WorldServices<'_> {
         }
 
         let target = Vec3::new(target_x, target_y, position.z);
-        if self.is_occupied(target) {
-            return Err(WorldError::tile_occupied());
+        if self.cuts_corner(&position, movement) || self.is_occupied(target) {
+            return Ok(StepOutcome::Blocked);
+        }
+        // The step is paced by the tile walked onto, even when it drops the character somewhere else.
+        let Some(step_time_percent) = self.walkable_step_time(&position, target) else {
+            return Ok(StepOutcome::Blocked);
+        };
+        if !self.house_services().can_pass_door(character.character_id, target) {
+            return Ok(StepOutcome::Blocked);
         }
 
-        if !self.is_walkable(&position, target) {
+        // Walking into a hole drops the character onto its linked tile below instead.
+        let destination = match self.find_floor_link(target) {
+            Some(link) if link.transition == FloorTransitionV1::Down => {
+                let below = Vec3::new(link.target_x, link.target_y, link.target_z);
+                self.ensure_can_enter(&position, below)?;
+                below
+            },
+            _ => target,
+        };
+
+        let arrives_at = self.compute_movement_arrives_at(character.character_id, movement, target, step_time_percent);
+        let landed = self.arrive(position, destination, movement, movement.into(), arrives_at)?;
+        Ok(StepOutcome::Moved(landed))
+    }
+
+    /// Places the character on `destination`, or past the portal standing there, and claims the temple it lands on.
+    fn arrive(
+        &self,
+        position: CharacterPositionV1,
+        destination: Vec3,
+        movement: MovementV1,
+        direction: DirectionV1,
+        arrives_at: Timestamp,
+    ) -> ServiceResult<Vec3> {
+        let character_id = position.character_id;
+        let destination = match self.find_portal(destination) {
+            Some(portal) => self.resolve_portal_arrival(character_id, &portal)?,
+            None => destination,
+        };
+
+        self.place_character(position, destination, movement, direction, arrives_at);
+        self.claim_citizenship_at(character_id, destination);
+        Ok(destination)
+    }
+
+    pub fn find_portal(&self, pos: Vec3) -> Option<PortalV1> {
+        self.db.portal_v1().map_id().find(pos.map_id())
+    }
+
+    /// Checks the portal requirements and picks a free tile around its destination.
+    fn resolve_portal_arrival(&self, character_id: u64, portal: &PortalV1) -> ServiceResult<Vec3> {
+        if let Some(min_level) = portal.min_level {
+            let level = self.character_services().find_stats(character_id).map(|stats| stats.level);
+            if level.is_none_or(|level| level < min_level) {
+                return Err(WorldError::portal_level_too_low(min_level));
+            }
+        }
+        if let Some(quest_id) = portal.required_quest_id
+            && !self.progression_services().has_completed_quest(character_id, quest_id)
+        {
+            return Err(WorldError::portal_quest_required());
+        }
+
+        let target = Vec3::new(portal.target_x, portal.target_y, portal.target_z);
+        let arrival = self
+            .find_free_tile_near(target, PORTAL_ARRIVAL_RADIUS)
+            .ok_or_else(WorldError::portal_destination_blocked)?;
+        if let Some(chunk) = self.find_map_at(arrival) {
+            self.remember_walked_chunk(character_id, &chunk);
+        }
+        Ok(arrival)
+    }
+
+    pub fn create_portal(
+        &self,
+        source: Vec3,
+        target: Vec3,
+        min_level: Option<u16>,
+        required_quest_id: Option<u64>,
+    ) -> ServiceResult<()> {
+        if !self.is_standable(source) || !self.is_standable(target) {
             return Err(WorldError::tile_not_walkable());
         }
 
-        let arrives_at = self.compute_movement_arrives_at(character.character_id, movement);
+        self.db.portal_v1().map_id().insert_or_update(PortalV1 {
+            map_id: source.map_id(),
+            sector_key: source.sector_key(),
+            target_x: target.x,
+            target_y: target.y,
+            target_z: target.z,
+            min_level,
+            required_quest_id,
+        });
+        Ok(())
+    }
+
+    pub fn remove_portal(&self, source: Vec3) -> ServiceResult<()> {
+        if !self.db.portal_v1().map_id().delete(source.map_id()) {
+            return Err(WorldError::portal_not_found(source));
+        }
+        Ok(())
+    }
 
+    fn place_character(
+        &self,
+        position: CharacterPositionV1,
+        destination: Vec3,
+        movement: MovementV1,
+        direction: DirectionV1,
+        arrives_at: Timestamp,
+    ) {
+        let character_id = position.character_id;
         let current_map_id = Vec3::new(position.x, position.y, position.z).map_id();
-        self.vacate_tile(current_map_id, position.character_id);
-        self.occupy_tile(target.map_id(), position.character_id);
+        self.vacate_tile(current_map_id, character_id);
+        self.occupy_tile(destination.map_id(), character_id);
 
         self.db
             .online_character_position_v1()
             .character_id()
             .update(CharacterPositionV1 {
-                character_id: position.character_id,
-                x: target.x,
-                y: target.y,
-                z: target.z,
+                character_id,
+                x: destination.x,
+                y: destination.y,
+                z: destination.z,
                 movement,
-                direction: movement.into(),
+                direction,
                 arrives_at,
             });
 
-        self.set_movement_cooldown(character.character_id, arrives_at);
-        Ok(())
+        self.set_movement_cooldown(character_id, arrives_at);
+        self.refresh_region_awareness(character_id, destination);
+
+        // The explored area only grows when the character crosses into another minimap block.
+        if minimap::block_of(Vec3::new(position.x, position.y, position.z)) != minimap::block_of(destination) {
+            self.explore_around(character_id, destination);
+        }
+    }
+
+    /// Marks the minimap blocks around `pos` as explored, writing only the sectors that gained new blocks.
+    fn explore_around(&self, character_id: u64, pos: Vec3) {
+        let rect = Rect::around(pos.into(), MINIMAP_EXPLORE_RADIUS);
+
+        for (sector_key, bits) in minimap::explored_bits(rect, pos.z) {
+            let existing = self
+                .db
+                .explored_sector_v1()
+                .character_id()
+                .filter(character_id)
+                .find(|explored| explored.sector_key == sector_key);
+
+            match existing {
+                Some(mut explored) => {
+                    if minimap::mark_explored(&mut explored.blocks, &bits) {
+                        self.db.explored_sector_v1().explored_sector_id().update(explored);
+                    }
+                },
+                None => {
+                    let mut blocks = Vec::new();
+                    minimap::mark_explored(&mut blocks, &bits);
+                    self.db.explored_sector_v1().insert(ExploredSectorV1 {
+                        explored_sector_id: 0,
+                        character_id,
+                        sector_key,
+                        blocks,
+                    });
+                },
+            }
+        }
     }
 
     fn schedule_movement_intention(&self, character_id: u64, movement: MovementV1, can_move_at: Timestamp) {
@@ -346,18 +1411,24 @@ impl WorldServices<'_> {
             });
     }
 
-    fn compute_movement_arrives_at(&self, character_id: u64, movement: MovementV1) -> Timestamp {
+    fn compute_movement_arrives_at(
+        &self,
+        character_id: u64,
+        movement: MovementV1,
+        target: Vec3,
+        step_time_percent: u16,
+    ) -> Timestamp {
         let speed = self
             .character_services()
             .find_stats(character_id)
-            .map(|s| s.speed as u64)
-            .unwrap_or(DEFAULT_CHARACTER_SPEED as u64);
+            .map(|s| s.speed)
+            .unwrap_or_else(|| self.config().starting_stats.speed);
+        let step_time_percent = self
+            .environment_services()
+            .weather_at(target)
+            .scale_step_time(step_time_percent);
 
-        let mut cooldown_ms = MOVEMENT_COOLDOWN_FACTOR / speed;
-        if movement.is_diagonal() {
-            cooldown_ms = cooldown_ms * 1_414_213 / 1_000_000;
-        }
-        self.timestamp + Duration::from_millis(cooldown_ms)
+        self.timestamp + Duration::from_millis(movement.step_duration_ms(speed, step_time_percent))
     }
 
     fn set_movement_cooldown(&self, character_id: u64, can_move_at: Timestamp) {
@@ -387,6 +1458,57 @@ enum WorldError {
 
     #[error("Movement is out of bounds")]
     MovementOutOfBounds,
+
+    #[error("Rect must have its minimum corner before its maximum corner and fit in the world")]
+    InvalidRect,
+
+    #[error("Ground must be a ground tile")]
+    NotGroundTile,
+
+    #[error("No floor link at ({}, {}, {})", .0.x, .0.y, .0.z)]
+    FloorLinkNotFound(Vec3),
+
+    #[error("Stair is too far away")]
+    StairOutOfReach,
+
+    #[error("No path to ({}, {}, {})", .0.x, .0.y, .0.z)]
+    PathNotFound(Vec3),
+
+    #[error("No portal at ({}, {}, {})", .0.x, .0.y, .0.z)]
+    PortalNotFound(Vec3),
+
+    #[error("Portal requires level {0}")]
+    PortalLevelTooLow(u16),
+
+    #[error("Portal requires a quest that has not been completed")]
+    PortalQuestRequired,
+
+    #[error("Portal destination is blocked")]
+    PortalDestinationBlocked,
+
+    #[error("Cannot log out here")]
+    LogoutBlocked,
+
+    #[error("Turning is on cooldown")]
+    TurnOnCooldown,
+
+    #[error("Cannot cut the corner diagonally")]
+    CornerBlocked,
+
+    #[error("Characters cannot push themselves")]
+    CannotPushSelf,
+
+    #[error("Push is on cooldown")]
+    PushOnCooldown,
+
+    #[error("Character is too far away to push")]
+    PushOutOfReach,
+
+    #[error("Characters cannot be pushed onto holes or portals")]
+    PushLeavesFloor,
+
+    #[error("Characters cannot be pushed out of a protection zone")]
+    PushOutOfProtectionZone,
 }
 
 impl WorldError {
@@ -409,4 +1531,72 @@ impl WorldError {
     fn movement_out_of_bounds() -> ServiceError {
         Self::MovementOutOfBounds.map_validation_error()
     }
+
+    fn invalid_rect() -> ServiceError {
+        Self::InvalidRect.map_validation_error()
+    }
+
+    fn not_ground_tile() -> ServiceError {
+        Self::NotGroundTile.map_validation_error()
+    }
+
+    fn floor_link_not_found(position: Vec3) -> ServiceError {
+        Self::FloorLinkNotFound(position).map_not_found_error()
+    }
+
+    fn stair_out_of_reach() -> ServiceError {
+        Self::StairOutOfReach.map_validation_error()
+    }
+
+    fn path_not_found(goal: Vec3) -> ServiceError {
+        Self::PathNotFound(goal).map_validation_error()
+    }
+
+    fn portal_not_found(position: Vec3) -> ServiceError {
+        Self::PortalNotFound(position).map_not_found_error()
+    }
+
+    fn portal_level_too_low(min_level: u16) -> ServiceError {
+        Self::PortalLevelTooLow(min_level).map_forbidden_error()
+    }
+
+    fn portal_quest_required() -> ServiceError {
+        Self::PortalQuestRequired.map_forbidden_error()
+    }
+
+    fn portal_destination_blocked() -> ServiceError {
+        Self::PortalDestinationBlocked.map_validation_error()
+    }
+
+    fn logout_blocked() -> ServiceError {
+        Self::LogoutBlocked.map_forbidden_error()
+    }
+
+    fn turn_on_cooldown() -> ServiceError {
+        Self::TurnOnCooldown.map_validation_error()
+    }
+
+    fn corner_blocked() -> ServiceError {
+        Self::CornerBlocked.map_validation_error()
+    }
+
+    fn cannot_push_self() -> ServiceError {
+        Self::CannotPushSelf.map_validation_error()
+    }
+
+    fn push_on_cooldown() -> ServiceError {
+        Self::PushOnCooldown.map_validation_error()
+    }
+
+    fn push_out_of_reach() -> ServiceError {
+        Self::PushOutOfReach.map_validation_error()
+    }
+
+    fn push_leaves_floor() -> ServiceError {
+        Self::PushLeavesFloor.map_validation_error()
+    }
+
+    fn push_out_of_protection_zone() -> ServiceError {
+        Self::PushOutOfProtectionZone.map_forbidden_error()
+    }
 }
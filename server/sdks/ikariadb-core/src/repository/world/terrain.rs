@@ -0,0 +1,323 @@
+use crate::{
+    error::{ErrorMapper, ServiceError, ServiceResult},
+    repository::world::types::{
+        MapBlueprint, MapChunk, MapTileStackV1, MapTileV1, Rect, RegionFlagsV1, RegionSeed, TempleSeed, TileGrid, Vec2, Vec3,
+    },
+};
+use thiserror::Error;
+
+const ELEVATION_PERIOD: u32 = 64;
+const ELEVATION_OCTAVES: u32 = 4;
+const LAKE_PERIOD: u32 = 48;
+const DETAIL_PERIOD: u32 = 24;
+const DETAIL_OCTAVES: u32 = 3;
+
+const SEA_LEVEL: f64 = 0.30;
+const BEACH_LEVEL: f64 = 0.34;
+const LAKE_LEVEL: f64 = 0.68;
+const LAKE_SHORE_LEVEL: f64 = 0.65;
+const DIRT_LEVEL: f64 = 0.66;
+const TREE_CHANCE_PERCENT: u64 = 4;
+const BUSH_CHANCE_PERCENT: u64 = 2;
+/// Name of the temple placed on the generated spawn.
+const TEMPLE_NAME: &str = "Haven";
+/// Half-width of the protection zone around the temple.
+const TEMPLE_PROTECTION_RADIUS: u16 = 4;
+/// Keeps the area around the spawn free of trees and bushes.
+const SPAWN_CLEARING_RADIUS: u16 = 3;
+
+const ELEVATION_SALT: u64 = 0x0E1E_0A71;
+const LAKE_SALT: u64 = 0x1A4E;
+const DETAIL_SALT: u64 = 0x00DE_7A11;
+const FOLIAGE_SALT: u64 = 0x0F01_1A6E;
+
+/// Inputs of the procedural generator; the same settings always produce the same terrain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerrainSettings {
+    pub seed: u64,
+    pub origin: Vec2,
+    pub width: u16,
+    pub height: u16,
+    pub z: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedTerrain {
+    pub blueprint: MapBlueprint,
+    /// Walkable tile closest to the middle of the generated area, also home to the only temple and its protection zone.
+    pub spawn: Vec3,
+}
+
+/// Generates a single island from layered value noise: the elevation sinks towards the edges so the border is
+/// always water, sand rings the coast and a second noise field carves lakes into the land.
+pub fn generate_terrain(settings: &TerrainSettings) -> ServiceResult<GeneratedTerrain> {
+    let width = settings.width as usize;
+    let height = settings.height as usize;
+    if width == 0 || height == 0 || settings.origin.x.checked_add(settings.width - 1).is_none() {
+        return Err(TerrainError::invalid_area());
+    }
+    if settings.origin.y.checked_add(settings.height - 1).is_none() {
+        return Err(TerrainError::invalid_area());
+    }
+
+    let mut grounds = vec![MapTileV1::Water; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            grounds[y * width + x] = ground_at(settings, x as u32, y as u32);
+        }
+    }
+
+    let spawn = find_spawn(&grounds, width, height).ok_or_else(TerrainError::no_walkable_tile)?;
+
+    let mut grid = TileGrid::new(settings.origin, width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let ground = grounds[y * width + x];
+            let mut tiles = MapTileStackV1::new(ground);
+            let near_spawn =
+                x.abs_diff(spawn.0) <= SPAWN_CLEARING_RADIUS as usize && y.abs_diff(spawn.1) <= SPAWN_CLEARING_RADIUS as usize;
+            if ground == MapTileV1::Grass && !near_spawn {
+                let roll = hash(settings.seed ^ FOLIAGE_SALT, x as u32, y as u32) % 100;
+                if roll < TREE_CHANCE_PERCENT {
+                    tiles = tiles.with_layer(MapTileV1::Tree);
+                } else if roll < TREE_CHANCE_PERCENT + BUSH_CHANCE_PERCENT {
+                    tiles = tiles.with_layer(MapTileV1::Bush);
+                }
+            }
+            grid.set(Vec2::new(settings.origin.x + x as u16, settings.origin.y + y as u16), tiles);
+        }
+    }
+
+    let chunks = grid
+        .merge_rects()
+        .into_iter()
+        .map(|(rect, tiles)| MapChunk {
+            rect,
+            z: settings.z,
+            tiles,
+        })
+        .collect();
+
+    let spawn = Vec3::new(
+        settings.origin.x + spawn.0 as u16,
+        settings.origin.y + spawn.1 as u16,
+        settings.z,
+    );
+    Ok(GeneratedTerrain {
+        blueprint: MapBlueprint {
+            chunks,
+            temples: vec![TempleSeed {
+                name: TEMPLE_NAME.to_string(),
+                position: spawn,
+            }],
+            regions: vec![RegionSeed {
+                rect: Rect::new(
+                    spawn.x.saturating_sub(TEMPLE_PROTECTION_RADIUS).max(settings.origin.x),
+                    spawn.y.saturating_sub(TEMPLE_PROTECTION_RADIUS).max(settings.origin.y),
+                    spawn
+                        .x
+                        .saturating_add(TEMPLE_PROTECTION_RADIUS)
+                        .min(settings.origin.x + (settings.width - 1)),
+                    spawn
+                        .y
+                        .saturating_add(TEMPLE_PROTECTION_RADIUS)
+                        .min(settings.origin.y + (settings.height - 1)),
+                ),
+                z: settings.z,
+                flags: RegionFlagsV1 {
+                    protection_zone: true,
+                    no_pvp: true,
+                    ..Default::default()
+                },
+            }],
+        },
+        spawn,
+    })
+}
+
+fn ground_at(settings: &TerrainSettings, x: u32, y: u32) -> MapTileV1 {
+    // Normalized distance from the middle of the area: 0 at the centre, 1 on the border.
+    let dx = (x as f64 + 0.5) / settings.width as f64 * 2.0 - 1.0;
+    let dy = (y as f64 + 0.5) / settings.height as f64 * 2.0 - 1.0;
+    let distance = dx.abs().max(dy.abs());
+
+    let elevation = fractal_noise(settings.seed ^ ELEVATION_SALT, x, y, ELEVATION_PERIOD, ELEVATION_OCTAVES)
+        - 0.8 * distance * distance * distance;
+    if elevation < SEA_LEVEL {
+        return MapTileV1::Water;
+    }
+    if elevation < BEACH_LEVEL {
+        return MapTileV1::Sand;
+    }
+
+    let lake = fractal_noise(settings.seed ^ LAKE_SALT, x, y, LAKE_PERIOD, DETAIL_OCTAVES);
+    if lake >= LAKE_LEVEL {
+        return MapTileV1::Water;
+    }
+    if lake >= LAKE_SHORE_LEVEL {
+        return MapTileV1::Sand;
+    }
+
+    if fractal_noise(settings.seed ^ DETAIL_SALT, x, y, DETAIL_PERIOD, DETAIL_OCTAVES) >= DIRT_LEVEL {
+        MapTileV1::Dirt
+    } else {
+        MapTileV1::Grass
+    }
+}
+
+/// Walks outwards ring by ring from the centre and returns the first walkable cell.
+fn find_spawn(grounds: &[MapTileV1], width: usize, height: usize) -> Option<(usize, usize)> {
+    let (cx, cy) = (width / 2, height / 2);
+    for radius in 0..=width.max(height) {
+        for y in cy.saturating_sub(radius)..=(cy + radius).min(height - 1) {
+            for x in cx.saturating_sub(radius)..=(cx + radius).min(width - 1) {
+                let on_ring = x.abs_diff(cx) == radius || y.abs_diff(cy) == radius;
+                if on_ring && MapTileStackV1::new(grounds[y * width + x]).is_walkable() {
+                    return Some((x, y));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Sum of `octaves` layers of value noise, each at half the period and half the weight of the previous one.
+fn fractal_noise(seed: u64, x: u32, y: u32, period: u32, octaves: u32) -> f64 {
+    let mut total = 0.0;
+    let mut weight = 1.0;
+    let mut weights = 0.0;
+    let mut period = period;
+    for octave in 0..octaves {
+        total += value_noise(seed.wrapping_add(octave as u64), x, y, period.max(1)) * weight;
+        weights += weight;
+        weight *= 0.5;
+        period /= 2;
+    }
+    total / weights
+}
+
+/// Smoothly interpolated lattice noise in `[0, 1)`.
+fn value_noise(seed: u64, x: u32, y: u32, period: u32) -> f64 {
+    let (cell_x, cell_y) = (x / period, y / period);
+    let tx = smoothstep((x % period) as f64 / period as f64);
+    let ty = smoothstep((y % period) as f64 / period as f64);
+
+    let corner = |cx: u32, cy: u32| (hash(seed, cx, cy) >> 11) as f64 / (1u64 << 53) as f64;
+    let top = lerp(corner(cell_x, cell_y), corner(cell_x + 1, cell_y), tx);
+    let bottom = lerp(corner(cell_x, cell_y + 1), corner(cell_x + 1, cell_y + 1), tx);
+    lerp(top, bottom, ty)
+}
+
+fn smoothstep(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+/// SplitMix64 finalizer over the seed and lattice coordinates.
+fn hash(seed: u64, x: u32, y: u32) -> u64 {
+    let mut z = seed ^ ((x as u64) << 32 | y as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[derive(Debug, Error)]
+enum TerrainError {
+    #[error("Terrain area must be non-empty and fit in the world")]
+    InvalidArea,
+
+    #[error("Generated terrain has no walkable tile to spawn on")]
+    NoWalkableTile,
+}
+
+impl TerrainError {
+    fn invalid_area() -> ServiceError {
+        Self::InvalidArea.map_validation_error()
+    }
+
+    fn no_walkable_tile() -> ServiceError {
+        Self::NoWalkableTile.map_validation_error()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(seed: u64) -> TerrainSettings {
+        TerrainSettings {
+            seed,
+            origin: Vec2::new(1000, 2000),
+            width: 160,
+            height: 128,
+            z: 7,
+        }
+    }
+
+    fn tile_at(terrain: &GeneratedTerrain, pos: Vec3) -> &MapTileStackV1 {
+        &terrain
+            .blueprint
+            .chunks
+            .iter()
+            .find(|chunk| chunk.z == pos.z && chunk.rect.contains(pos.into()))
+            .unwrap()
+            .tiles
+    }
+
+    #[test]
+    fn generate_terrain_is_deterministic_per_seed() {
+        let first = generate_terrain(&settings(42)).unwrap();
+
+        assert_eq!(first, generate_terrain(&settings(42)).unwrap());
+        assert_ne!(first.blueprint, generate_terrain(&settings(43)).unwrap().blueprint);
+    }
+
+    #[test]
+    fn generate_terrain_covers_the_area_once() {
+        let terrain = generate_terrain(&settings(7)).unwrap();
+
+        let area: u32 = terrain.blueprint.chunks.iter().map(|chunk| chunk.rect.area()).sum();
+        assert_eq!(area, 160 * 128);
+        let bounds = Rect::new(1000, 2000, 1159, 2127);
+        assert!(
+            terrain
+                .blueprint
+                .chunks
+                .iter()
+                .all(|chunk| bounds.intersection(&chunk.rect) == Some(chunk.rect))
+        );
+    }
+
+    #[test]
+    fn generate_terrain_surrounds_a_walkable_spawn_with_water() {
+        for seed in 0..8 {
+            let terrain = generate_terrain(&settings(seed)).unwrap();
+
+            assert!(tile_at(&terrain, terrain.spawn).is_walkable());
+            for x in 1000..1160 {
+                assert_eq!(tile_at(&terrain, Vec3::new(x, 2000, 7)).ground, MapTileV1::Water);
+                assert_eq!(tile_at(&terrain, Vec3::new(x, 2127, 7)).ground, MapTileV1::Water);
+            }
+        }
+    }
+
+    #[test]
+    fn generate_terrain_mixes_lakes_beaches_and_land() {
+        let terrain = generate_terrain(&settings(1)).unwrap();
+
+        for ground in [MapTileV1::Water, MapTileV1::Sand, MapTileV1::Grass] {
+            assert!(terrain.blueprint.chunks.iter().any(|chunk| chunk.tiles.ground == ground));
+        }
+    }
+
+    #[test]
+    fn generate_terrain_rejects_areas_outside_the_world() {
+        let mut out_of_world = settings(1);
+        out_of_world.origin = Vec2::new(u16::MAX - 10, 0);
+
+        assert!(matches!(generate_terrain(&out_of_world), Err(ServiceError::Validation(_))));
+    }
+}
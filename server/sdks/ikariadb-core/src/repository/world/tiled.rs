@@ -0,0 +1,691 @@
+use crate::{
+    constants::SECTOR_SIZE,
+    error::{ErrorMapper, ServiceError, ServiceResult},
+    repository::world::types::{
+        MapBlueprint, MapChunk, MapTileStackV1, MapTileV1, Rect, RegionFlagsV1, RegionSeed, TempleSeed, TileGrid, Vec2, Vec3,
+    },
+};
+use ikaria_shared::constants::GROUND_LEVEL;
+use serde::{Deserialize, de::IgnoredAny};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use thiserror::Error;
+
+/// Tiled stores flip/rotation flags in the upper bits of every gid.
+const TILED_GID_MASK: u32 = 0x0FFF_FFFF;
+const TILED_DEFAULT_TILE_SIZE: i64 = 32;
+const TEMPLE_OBJECT_CLASS: &str = "Temple";
+const REGION_OBJECT_CLASS: &str = "Region";
+/// Upper bound on grid cells rasterized for one import, summed over the sector blocks that hold tiles.
+const MAX_IMPORT_CELLS: usize = 4096 * 4096;
+
+#[derive(Debug, Deserialize)]
+struct TiledMap {
+    #[serde(default = "default_tile_size")]
+    tilewidth: i64,
+    #[serde(default = "default_tile_size")]
+    tileheight: i64,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+    #[serde(default)]
+    layers: Vec<TiledLayer>,
+    #[serde(default)]
+    tilesets: Vec<TiledTileset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TiledProperty {
+    name: String,
+    #[serde(default)]
+    value: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct TiledLayer {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    x: i64,
+    #[serde(default)]
+    y: i64,
+    #[serde(default)]
+    width: i64,
+    #[serde(default)]
+    data: Option<TiledData>,
+    #[serde(default)]
+    chunks: Vec<TiledChunk>,
+    #[serde(default)]
+    encoding: Option<String>,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+    #[serde(default)]
+    layers: Vec<TiledLayer>,
+    #[serde(default)]
+    objects: Vec<TiledObject>,
+}
+
+/// Point object placed in an object layer; coordinates are in pixels.
+#[derive(Debug, Deserialize)]
+struct TiledObject {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    class: Option<String>,
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+    x: f64,
+    y: f64,
+    #[serde(default)]
+    width: f64,
+    #[serde(default)]
+    height: f64,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TiledData {
+    Csv(Vec<u32>),
+    Encoded(IgnoredAny),
+}
+
+#[derive(Debug, Deserialize)]
+struct TiledChunk {
+    x: i64,
+    y: i64,
+    width: i64,
+    data: TiledData,
+}
+
+#[derive(Debug, Deserialize)]
+struct TiledTileset {
+    firstgid: u32,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    tiles: Vec<TiledTile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TiledTile {
+    id: u32,
+    #[serde(default)]
+    class: Option<String>,
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+}
+
+/// Parses a Tiled JSON export into a map blueprint.
+///
+/// Map properties `origin_x`/`origin_y` place the map in world coordinates and `z` sets its floor; tile layers may
+/// override `z` with their own property. Each tileset tile must carry a class naming a `MapTileV1` variant.
+/// Layers sharing a floor are stacked in order: ground tiles replace the ground and any other tile is layered on top.
+/// Named objects of class `Temple` in object layers become town temples on the tile they sit on, and rectangle objects
+/// of class `Region` flag every tile they touch with their `protection_zone`, `no_pvp`, `no_logout` and `house`
+/// boolean properties.
+pub fn parse_tiled_map(source: &str) -> ServiceResult<MapBlueprint> {
+    let map: TiledMap = serde_json::from_str(source).map_err(|err| TiledMapError::invalid_format(err.to_string()))?;
+
+    let tiles = resolve_tilesets(&map.tilesets)?;
+    let origin_x = int_property(&map.properties, "origin_x").unwrap_or(0);
+    let origin_y = int_property(&map.properties, "origin_y").unwrap_or(0);
+    let map_z = int_property(&map.properties, "z").unwrap_or(GROUND_LEVEL as i64);
+
+    let mut cells: BTreeMap<u8, BTreeMap<(u16, u16), CellTiles>> = BTreeMap::new();
+    let mut unknown = BTreeSet::new();
+
+    for layer in flatten_layers(&map.layers, "tilelayer") {
+        let z = int_property(&layer.properties, "z").unwrap_or(map_z);
+        let z = u8::try_from(z).map_err(|_| TiledMapError::out_of_bounds(&layer.name))?;
+        let floor = cells.entry(z).or_default();
+
+        for (x, y, gid) in layer_tiles(layer)? {
+            if gid == 0 {
+                continue;
+            }
+            let Some(&tile) = tiles.get(&gid) else {
+                unknown.insert(gid);
+                continue;
+            };
+            let x = u16::try_from(origin_x + x).map_err(|_| TiledMapError::out_of_bounds(&layer.name))?;
+            let y = u16::try_from(origin_y + y).map_err(|_| TiledMapError::out_of_bounds(&layer.name))?;
+            let cell = floor.entry((x, y)).or_default();
+            if tile.is_ground() {
+                cell.ground = Some(tile);
+            } else {
+                cell.layers.push(tile);
+            }
+        }
+    }
+
+    if !unknown.is_empty() {
+        return Err(TiledMapError::unknown_tile_ids(unknown.into_iter().collect()));
+    }
+
+    let mut blueprint = MapBlueprint::default();
+    let mut blocks = Vec::new();
+    for (z, floor) in cells {
+        for (_, block) in split_by_sector(floor) {
+            blocks.push((z, block));
+        }
+    }
+    let cell_count: usize = blocks.iter().map(|(_, block)| bounding_area(block)).sum();
+    if cell_count > MAX_IMPORT_CELLS {
+        return Err(TiledMapError::too_large(cell_count));
+    }
+    for (z, block) in blocks {
+        let Some(grid) = build_grid(z, block)? else {
+            continue;
+        };
+        for (rect, tiles) in grid.merge_rects() {
+            blueprint.chunks.push(MapChunk { rect, z, tiles });
+        }
+    }
+
+    for layer in flatten_layers(&map.layers, "objectgroup") {
+        let z = int_property(&layer.properties, "z").unwrap_or(map_z);
+        let z = u8::try_from(z).map_err(|_| TiledMapError::out_of_bounds(&layer.name))?;
+
+        let to_world = |x: f64, y: f64| -> ServiceResult<Vec2> {
+            let x = origin_x + (x / map.tilewidth.max(1) as f64).floor() as i64;
+            let y = origin_y + (y / map.tileheight.max(1) as f64).floor() as i64;
+            let x = u16::try_from(x).map_err(|_| TiledMapError::out_of_bounds(&layer.name))?;
+            let y = u16::try_from(y).map_err(|_| TiledMapError::out_of_bounds(&layer.name))?;
+            Ok(Vec2::new(x, y))
+        };
+
+        for object in &layer.objects {
+            match object.class.as_deref().or(object.kind.as_deref()) {
+                Some(TEMPLE_OBJECT_CLASS) => {
+                    if object.name.is_empty() {
+                        return Err(TiledMapError::unnamed_temple(&layer.name));
+                    }
+                    blueprint.temples.push(TempleSeed {
+                        name: object.name.clone(),
+                        position: to_world(object.x, object.y)?.with_z(z),
+                    });
+                },
+                Some(REGION_OBJECT_CLASS) => {
+                    // The far edge is exclusive, so step back a hair to stay on the last covered tile.
+                    let min = to_world(object.x, object.y)?;
+                    let max = to_world(
+                        object.x + (object.width - 0.5).max(0.0),
+                        object.y + (object.height - 0.5).max(0.0),
+                    )?;
+                    blueprint.regions.push(RegionSeed {
+                        rect: Rect::new(min.x, min.y, max.x, max.y),
+                        z,
+                        flags: RegionFlagsV1 {
+                            protection_zone: bool_property(&object.properties, "protection_zone"),
+                            no_pvp: bool_property(&object.properties, "no_pvp"),
+                            no_logout: bool_property(&object.properties, "no_logout"),
+                            house: bool_property(&object.properties, "house"),
+                        },
+                    });
+                },
+                _ => {},
+            }
+        }
+    }
+
+    Ok(blueprint)
+}
+
+fn default_tile_size() -> i64 {
+    TILED_DEFAULT_TILE_SIZE
+}
+
+fn resolve_tilesets(tilesets: &[TiledTileset]) -> ServiceResult<HashMap<u32, MapTileV1>> {
+    let mut tiles = HashMap::new();
+    for tileset in tilesets {
+        if let Some(source) = &tileset.source {
+            return Err(TiledMapError::external_tileset(source.clone()));
+        }
+
+        for tile in &tileset.tiles {
+            let Some(class) = tile.class.as_deref().or(tile.kind.as_deref()) else {
+                continue;
+            };
+            let resolved = MapTileV1::from_name(class).ok_or_else(|| TiledMapError::unknown_tile_class(class))?;
+            tiles.insert(tileset.firstgid + tile.id, resolved);
+        }
+    }
+    Ok(tiles)
+}
+
+fn flatten_layers<'a>(layers: &'a [TiledLayer], kind: &str) -> Vec<&'a TiledLayer> {
+    let mut flattened = Vec::new();
+    for layer in layers {
+        if layer.kind == "group" {
+            flattened.extend(flatten_layers(&layer.layers, kind));
+        } else if layer.kind == kind {
+            flattened.push(layer);
+        }
+    }
+    flattened
+}
+
+fn layer_tiles(layer: &TiledLayer) -> ServiceResult<Vec<(i64, i64, u32)>> {
+    if layer.encoding.as_deref().is_some_and(|encoding| encoding != "csv") {
+        return Err(TiledMapError::unsupported_encoding(&layer.name));
+    }
+
+    let mut tiles = Vec::new();
+    let mut push_block = |x: i64, y: i64, width: i64, data: &TiledData| -> ServiceResult<()> {
+        let TiledData::Csv(data) = data else {
+            return Err(TiledMapError::unsupported_encoding(&layer.name));
+        };
+        if width <= 0 {
+            return Ok(());
+        }
+        for (index, &gid) in data.iter().enumerate() {
+            let index = index as i64;
+            tiles.push((x + index % width, y + index / width, gid & TILED_GID_MASK));
+        }
+        Ok(())
+    };
+
+    if let Some(data) = &layer.data {
+        push_block(layer.x, layer.y, layer.width, data)?;
+    }
+    for chunk in &layer.chunks {
+        push_block(layer.x + chunk.x, layer.y + chunk.y, chunk.width, &chunk.data)?;
+    }
+
+    Ok(tiles)
+}
+
+#[derive(Debug, Default)]
+struct CellTiles {
+    ground: Option<MapTileV1>,
+    layers: Vec<MapTileV1>,
+}
+
+/// Groups a floor's cells by sector so far-apart islands never share one dense grid.
+fn split_by_sector(floor: BTreeMap<(u16, u16), CellTiles>) -> BTreeMap<(u16, u16), BTreeMap<(u16, u16), CellTiles>> {
+    let mut blocks: BTreeMap<(u16, u16), BTreeMap<(u16, u16), CellTiles>> = BTreeMap::new();
+    for ((x, y), cell) in floor {
+        blocks
+            .entry((x / SECTOR_SIZE, y / SECTOR_SIZE))
+            .or_default()
+            .insert((x, y), cell);
+    }
+    blocks
+}
+
+fn bounding_area(cells: &BTreeMap<(u16, u16), CellTiles>) -> usize {
+    let (Some(min_x), Some(max_x)) = (cells.keys().map(|&(x, _)| x).min(), cells.keys().map(|&(x, _)| x).max()) else {
+        return 0;
+    };
+    let min_y = cells.keys().map(|&(_, y)| y).min().unwrap_or_default();
+    let max_y = cells.keys().map(|&(_, y)| y).max().unwrap_or_default();
+    ((max_x - min_x) as usize + 1) * ((max_y - min_y) as usize + 1)
+}
+
+fn build_grid(z: u8, floor: BTreeMap<(u16, u16), CellTiles>) -> ServiceResult<Option<TileGrid<MapTileStackV1>>> {
+    let Some(min_x) = floor.keys().map(|&(x, _)| x).min() else {
+        return Ok(None);
+    };
+    let min_y = floor.keys().map(|&(_, y)| y).min().unwrap_or_default();
+    let max_x = floor.keys().map(|&(x, _)| x).max().unwrap_or_default();
+    let max_y = floor.keys().map(|&(_, y)| y).max().unwrap_or_default();
+
+    let width = (max_x - min_x) as usize + 1;
+    let height = (max_y - min_y) as usize + 1;
+    let mut grid = TileGrid::new(Vec2::new(min_x, min_y), width, height);
+    for ((x, y), cell) in floor {
+        let Some(ground) = cell.ground else {
+            return Err(TiledMapError::missing_ground(Vec3::new(x, y, z)));
+        };
+        grid.set(
+            Vec2::new(x, y),
+            MapTileStackV1 {
+                ground,
+                layers: cell.layers,
+            },
+        );
+    }
+    Ok(Some(grid))
+}
+
+fn bool_property(properties: &[TiledProperty], name: &str) -> bool {
+    properties
+        .iter()
+        .find(|property| property.name == name)
+        .and_then(|property| property.value.as_bool())
+        .unwrap_or(false)
+}
+
+fn int_property(properties: &[TiledProperty], name: &str) -> Option<i64> {
+    properties
+        .iter()
+        .find(|property| property.name == name)
+        .and_then(|property| property.value.as_i64())
+}
+
+#[derive(Debug, Error)]
+enum TiledMapError {
+    #[error("Tiled map is not a valid JSON export: {0}")]
+    InvalidFormat(String),
+
+    #[error("External tileset '{0}' is not supported, embed it in the map")]
+    ExternalTileset(String),
+
+    #[error("Layer '{0}' must be exported with CSV tile layer format")]
+    UnsupportedEncoding(String),
+
+    #[error("Layer '{0}' places tiles outside of the world bounds")]
+    OutOfBounds(String),
+
+    #[error("Tile class '{0}' does not match any map tile")]
+    UnknownTileClass(String),
+
+    #[error("Unknown tile ids: {0:?}")]
+    UnknownTileIds(Vec<u32>),
+
+    #[error("Tile at ({}, {}, {}) has layers but no ground", .0.x, .0.y, .0.z)]
+    MissingGround(Vec3),
+
+    #[error("Layer '{0}' has a temple without a name")]
+    UnnamedTemple(String),
+
+    #[error("Map spans {0} cells, more than the {MAX_IMPORT_CELLS} allowed per import")]
+    TooLarge(usize),
+}
+
+impl TiledMapError {
+    fn invalid_format(message: String) -> ServiceError {
+        Self::InvalidFormat(message).map_validation_error()
+    }
+
+    fn external_tileset(source: String) -> ServiceError {
+        Self::ExternalTileset(source).map_validation_error()
+    }
+
+    fn unsupported_encoding(layer: &str) -> ServiceError {
+        Self::UnsupportedEncoding(layer.to_string()).map_validation_error()
+    }
+
+    fn out_of_bounds(layer: &str) -> ServiceError {
+        Self::OutOfBounds(layer.to_string()).map_validation_error()
+    }
+
+    fn unknown_tile_class(class: &str) -> ServiceError {
+        Self::UnknownTileClass(class.to_string()).map_validation_error()
+    }
+
+    fn unknown_tile_ids(gids: Vec<u32>) -> ServiceError {
+        Self::UnknownTileIds(gids).map_validation_error()
+    }
+
+    fn missing_ground(position: Vec3) -> ServiceError {
+        Self::MissingGround(position).map_validation_error()
+    }
+
+    fn unnamed_temple(layer: &str) -> ServiceError {
+        Self::UnnamedTemple(layer.to_string()).map_validation_error()
+    }
+
+    fn too_large(cell_count: usize) -> ServiceError {
+        Self::TooLarge(cell_count).map_validation_error()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{DEFAULT_SPAWN_X, DEFAULT_SPAWN_Y, INITIAL_MAP};
+
+    const TILESET: &str = r#"{"firstgid": 1, "tiles": [
+        {"id": 0, "class": "Water"}, {"id": 1, "type": "Grass"}, {"id": 2, "class": "GrassBorder"}, {"id": 3, "class": "Tree"}
+    ]}"#;
+
+    fn map(properties: &str, layers: &str) -> String {
+        format!(r#"{{"properties": [{properties}], "tilesets": [{TILESET}], "layers": [{layers}]}}"#)
+    }
+
+    #[test]
+    fn parse_tiled_map_merges_layer_into_rects() {
+        let source = map(
+            r#"{"name": "origin_x", "value": 100}, {"name": "origin_y", "value": 200}"#,
+            r#"{"name": "ground", "type": "tilelayer", "width": 3, "height": 2, "data": [2, 2, 1, 2, 2, 1]}"#,
+        );
+
+        let blueprint = parse_tiled_map(&source).unwrap();
+
+        assert_eq!(
+            blueprint.chunks,
+            vec![
+                MapChunk {
+                    rect: Rect::new(100, 200, 101, 201),
+                    z: GROUND_LEVEL,
+                    tiles: MapTileStackV1::new(MapTileV1::Grass),
+                },
+                MapChunk {
+                    rect: Rect::new(102, 200, 102, 201),
+                    z: GROUND_LEVEL,
+                    tiles: MapTileStackV1::new(MapTileV1::Water),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_tiled_map_reads_infinite_chunks_and_layer_z() {
+        let source = map(
+            "",
+            r#"{"name": "cave", "type": "tilelayer", "properties": [{"name": "z", "value": 128}],
+                "chunks": [{"x": 16, "y": 0, "width": 2, "height": 1, "data": [0, 2]}]}"#,
+        );
+
+        let blueprint = parse_tiled_map(&source).unwrap();
+
+        assert_eq!(
+            blueprint.chunks,
+            vec![MapChunk {
+                rect: Rect::new(17, 0, 17, 0),
+                z: 128,
+                tiles: MapTileStackV1::new(MapTileV1::Grass),
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_tiled_map_keeps_far_apart_chunks_in_separate_grids() {
+        let source = map(
+            "",
+            r#"{"name": "ground", "type": "tilelayer",
+                "chunks": [{"x": 0, "y": 0, "width": 1, "height": 1, "data": [2]},
+                           {"x": 65000, "y": 65000, "width": 1, "height": 1, "data": [2]}]}"#,
+        );
+
+        let blueprint = parse_tiled_map(&source).unwrap();
+
+        let rects: Vec<_> = blueprint.chunks.iter().map(|chunk| chunk.rect).collect();
+        assert_eq!(rects, vec![Rect::new(0, 0, 0, 0), Rect::new(65000, 65000, 65000, 65000)]);
+    }
+
+    #[test]
+    fn parse_tiled_map_stacks_layers_on_ground() {
+        let source = map(
+            "",
+            r#"{"name": "ground", "type": "tilelayer", "width": 2, "data": [2, 2]},
+               {"name": "borders", "type": "tilelayer", "width": 2, "data": [3, 0]},
+               {"name": "trees", "type": "tilelayer", "width": 2, "data": [4, 0]}"#,
+        );
+
+        let blueprint = parse_tiled_map(&source).unwrap();
+
+        assert_eq!(
+            blueprint.chunks[0].tiles,
+            MapTileStackV1::new(MapTileV1::Grass)
+                .with_layer(MapTileV1::GrassBorder)
+                .with_layer(MapTileV1::Tree)
+        );
+        assert_eq!(blueprint.chunks[1].tiles, MapTileStackV1::new(MapTileV1::Grass));
+    }
+
+    #[test]
+    fn parse_tiled_map_rejects_layers_without_ground() {
+        let source = map("", r#"{"name": "trees", "type": "tilelayer", "width": 1, "data": [4]}"#);
+
+        assert!(matches!(parse_tiled_map(&source), Err(ServiceError::Validation(_))));
+    }
+
+    #[test]
+    fn parse_tiled_map_ignores_flip_flags() {
+        let flipped = 0x8000_0000u32 | 2;
+        let source = map("", &format!(r#"{{"type": "tilelayer", "width": 1, "data": [{flipped}]}}"#));
+
+        assert_eq!(parse_tiled_map(&source).unwrap().chunks[0].tiles.ground, MapTileV1::Grass);
+    }
+
+    #[test]
+    fn parse_tiled_map_reports_unknown_tile_ids() {
+        let source = map("", r#"{"type": "tilelayer", "width": 3, "data": [2, 9, 7]}"#);
+
+        let err = parse_tiled_map(&source).unwrap_err();
+        assert!(matches!(err, ServiceError::Validation(message) if message.contains("[7, 9]")));
+    }
+
+    #[test]
+    fn parse_tiled_map_reads_temples_from_object_layers() {
+        let source = map(
+            r#"{"name": "origin_x", "value": 100}, {"name": "origin_y", "value": 200}"#,
+            r#"{"type": "tilelayer", "width": 1, "data": [2]},
+               {"type": "group", "layers": [{"name": "towns", "type": "objectgroup", "objects": [
+                   {"name": "Riverside", "class": "Temple", "x": 80, "y": 40, "point": true},
+                   {"name": "Sign", "class": "Note", "x": 0, "y": 0}
+               ]}]}"#,
+        );
+
+        let blueprint = parse_tiled_map(&source).unwrap();
+
+        assert_eq!(
+            blueprint.temples,
+            vec![TempleSeed {
+                name: "Riverside".to_string(),
+                position: Vec3::new(102, 201, GROUND_LEVEL),
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_tiled_map_reads_region_rectangles() {
+        let source = map(
+            r#"{"name": "origin_x", "value": 100}, {"name": "z", "value": 9}"#,
+            r#"{"name": "zones", "type": "objectgroup", "objects": [
+                {"class": "Region", "x": 32, "y": 64, "width": 96, "height": 32, "properties": [
+                    {"name": "protection_zone", "type": "bool", "value": true},
+                    {"name": "no_logout", "type": "bool", "value": false}
+                ]}
+            ]}"#,
+        );
+
+        let blueprint = parse_tiled_map(&source).unwrap();
+
+        assert_eq!(
+            blueprint.regions,
+            vec![RegionSeed {
+                rect: Rect::new(101, 2, 103, 2),
+                z: 9,
+                flags: RegionFlagsV1 {
+                    protection_zone: true,
+                    ..Default::default()
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_tiled_map_rejects_unnamed_temples() {
+        let source = map(
+            "",
+            r#"{"name": "towns", "type": "objectgroup", "objects": [{"type": "Temple", "x": 0, "y": 0}]}"#,
+        );
+
+        assert!(matches!(parse_tiled_map(&source), Err(ServiceError::Validation(_))));
+    }
+
+    #[test]
+    fn parse_tiled_map_rejects_base64_layers() {
+        let source = map(
+            "",
+            r#"{"name": "ground", "type": "tilelayer", "width": 1, "encoding": "base64", "data": "AQAAAA=="}"#,
+        );
+
+        assert!(matches!(parse_tiled_map(&source), Err(ServiceError::Validation(_))));
+    }
+
+    #[test]
+    fn parse_tiled_map_rejects_tiles_before_world_origin() {
+        let source = map(
+            r#"{"name": "origin_x", "value": -1}"#,
+            r#"{"name": "ground", "type": "tilelayer", "width": 1, "data": [1]}"#,
+        );
+
+        assert!(matches!(parse_tiled_map(&source), Err(ServiceError::Validation(_))));
+    }
+
+    #[test]
+    fn initial_map_is_a_grass_island_surrounded_by_water() {
+        let blueprint = parse_tiled_map(INITIAL_MAP).unwrap();
+
+        let island = Rect::new(1024, 1024, 1280, 1280);
+        let ground_level: Vec<_> = blueprint.chunks.iter().filter(|chunk| chunk.z == GROUND_LEVEL).collect();
+        assert!(
+            ground_level
+                .iter()
+                .all(|chunk| (chunk.tiles.ground == MapTileV1::Water) != island.overlaps(&chunk.rect))
+        );
+        assert!(
+            ground_level
+                .iter()
+                .all(|chunk| chunk.tiles.ground != MapTileV1::Grass || chunk.rect.intersection(&island) == Some(chunk.rect))
+        );
+    }
+
+    #[test]
+    fn initial_map_links_ground_level_to_a_lookout_above() {
+        let blueprint = parse_tiled_map(INITIAL_MAP).unwrap();
+
+        assert!(blueprint.chunks.contains(&MapChunk {
+            rect: Rect::new(1160, 1152, 1160, 1152),
+            z: GROUND_LEVEL,
+            tiles: MapTileStackV1::new(MapTileV1::Grass).with_layer(MapTileV1::StairUp),
+        }));
+        assert!(blueprint.chunks.contains(&MapChunk {
+            rect: Rect::new(1163, 1150, 1163, 1150),
+            z: GROUND_LEVEL + 1,
+            tiles: MapTileStackV1::new(MapTileV1::StoneFloor).with_layer(MapTileV1::Hole),
+        }));
+        let lookout = blueprint.chunks.iter().filter(|chunk| chunk.z == GROUND_LEVEL + 1);
+        assert!(
+            lookout
+                .clone()
+                .all(|chunk| Rect::new(1158, 1148, 1164, 1154).intersection(&chunk.rect) == Some(chunk.rect))
+        );
+        assert_eq!(lookout.map(|chunk| chunk.rect.area()).sum::<u32>(), 49);
+    }
+
+    #[test]
+    fn initial_map_places_a_protected_temple_on_the_spawn() {
+        let blueprint = parse_tiled_map(INITIAL_MAP).unwrap();
+
+        assert_eq!(blueprint.regions.len(), 1);
+        let temple_zone = &blueprint.regions[0];
+        assert!(temple_zone.flags.protection_zone);
+        assert!(temple_zone.rect.contains(Vec2::new(DEFAULT_SPAWN_X, DEFAULT_SPAWN_Y)));
+
+        assert_eq!(
+            blueprint.temples,
+            vec![TempleSeed {
+                name: "Ikaria".to_string(),
+                position: Vec3::new(DEFAULT_SPAWN_X, DEFAULT_SPAWN_Y, GROUND_LEVEL),
+            }]
+        );
+    }
+}
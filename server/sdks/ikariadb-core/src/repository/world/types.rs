@@ -1,8 +1,9 @@
 use crate::{
-    constants::SECTOR_SIZE,
-    repository::world::{MapV1, WalkedMapChunkV1},
+    constants::{BASE_STEP_TIME_PERCENT, MOVEMENT_COOLDOWN_FACTOR, SECTOR_SIZE},
+    repository::world::{CharacterPositionV1, FloorLinkV1, MapRegionV1, MapV1, WalkCacheStatsV1, WalkedMapChunkV1},
 };
 use spacetimedb::SpacetimeType;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Vec2 {
@@ -61,6 +62,11 @@ impl Vec3 {
         let sector_y = self.y / SECTOR_SIZE;
         ((self.z as u64) << 32) | ((sector_x as u64) << 16) | (sector_y as u64)
     }
+
+    /// Same floor and at most one tile away in any direction, including diagonals.
+    pub fn is_adjacent_to(&self, other: Vec3) -> bool {
+        self.z == other.z && self.x.abs_diff(other.x) <= 1 && self.y.abs_diff(other.y) <= 1
+    }
 }
 
 impl From<(u16, u16, u8)> for Vec3 {
@@ -83,6 +89,16 @@ impl Rect {
         }
     }
 
+    /// Square of side `2 * radius + 1` centred on `center`, clamped to the world edges.
+    pub fn around(center: Vec2, radius: u16) -> Self {
+        Self::new(
+            center.x.saturating_sub(radius),
+            center.y.saturating_sub(radius),
+            center.x.saturating_add(radius),
+            center.y.saturating_add(radius),
+        )
+    }
+
     pub fn contains(&self, point: Vec2) -> bool {
         point.x >= self.min.x && point.x <= self.max.x && point.y >= self.min.y && point.y <= self.max.y
     }
@@ -90,6 +106,82 @@ impl Rect {
     pub fn overlaps(&self, other: &Rect) -> bool {
         self.min.x <= other.max.x && self.max.x >= other.min.x && self.min.y <= other.max.y && self.max.y >= other.min.y
     }
+
+    pub fn is_valid(&self) -> bool {
+        self.min.x <= self.max.x && self.min.y <= self.max.y
+    }
+
+    pub fn area(&self) -> u32 {
+        (self.max.x - self.min.x + 1) as u32 * (self.max.y - self.min.y + 1) as u32
+    }
+
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.overlaps(other) {
+            return None;
+        }
+        Some(Rect::new(
+            self.min.x.max(other.min.x),
+            self.min.y.max(other.min.y),
+            self.max.x.min(other.max.x),
+            self.max.y.min(other.max.y),
+        ))
+    }
+
+    /// Splits `self` into the pieces left after removing `other`: full-width top and bottom bands,
+    /// then the left and right remainders of the middle band.
+    pub fn subtract(&self, other: &Rect) -> Vec<Rect> {
+        let Some(cut) = self.intersection(other) else {
+            return vec![*self];
+        };
+
+        let mut pieces = Vec::with_capacity(4);
+        if cut.min.y > self.min.y {
+            pieces.push(Rect::new(self.min.x, self.min.y, self.max.x, cut.min.y - 1));
+        }
+        if cut.max.y < self.max.y {
+            pieces.push(Rect::new(self.min.x, cut.max.y + 1, self.max.x, self.max.y));
+        }
+        if cut.min.x > self.min.x {
+            pieces.push(Rect::new(self.min.x, cut.min.y, cut.min.x - 1, cut.max.y));
+        }
+        if cut.max.x < self.max.x {
+            pieces.push(Rect::new(cut.max.x + 1, cut.min.y, self.max.x, cut.max.y));
+        }
+        pieces
+    }
+
+    /// Cuts the rect along sector borders so every piece can be stored under a single `sector_key`.
+    pub fn split_by_sector(&self) -> Vec<Rect> {
+        let mut pieces = Vec::new();
+        if !self.is_valid() {
+            return pieces;
+        }
+
+        let mut x1 = self.min.x;
+        loop {
+            let x2 = (x1 / SECTOR_SIZE)
+                .saturating_mul(SECTOR_SIZE)
+                .saturating_add(SECTOR_SIZE - 1)
+                .min(self.max.x);
+            let mut y1 = self.min.y;
+            loop {
+                let y2 = (y1 / SECTOR_SIZE)
+                    .saturating_mul(SECTOR_SIZE)
+                    .saturating_add(SECTOR_SIZE - 1)
+                    .min(self.max.y);
+                pieces.push(Rect::new(x1, y1, x2, y2));
+                if y2 == self.max.y {
+                    break;
+                }
+                y1 = y2 + 1;
+            }
+            if x2 == self.max.x {
+                break;
+            }
+            x1 = x2 + 1;
+        }
+        pieces
+    }
 }
 
 impl From<&MapV1> for Rect {
@@ -98,12 +190,29 @@ impl From<&MapV1> for Rect {
     }
 }
 
+impl From<&MapRegionV1> for Rect {
+    fn from(region: &MapRegionV1) -> Self {
+        Self::new(region.x1, region.y1, region.x2, region.y2)
+    }
+}
+
 impl From<&WalkedMapChunkV1> for Rect {
     fn from(cache: &WalkedMapChunkV1) -> Self {
         Self::new(cache.x1, cache.y1, cache.x2, cache.y2)
     }
 }
 
+impl WalkCacheStatsV1 {
+    pub fn new(walk_cache_stats_id: u64) -> Self {
+        Self {
+            walk_cache_stats_id,
+            misses: 0,
+            evictions: 0,
+            invalidations: 0,
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, SpacetimeType)]
 pub enum DirectionV1 {
     North,
@@ -138,10 +247,37 @@ pub enum MovementV1 {
 }
 
 impl MovementV1 {
+    pub const ALL: [MovementV1; 8] = [
+        MovementV1::North,
+        MovementV1::NorthEast,
+        MovementV1::East,
+        MovementV1::SouthEast,
+        MovementV1::South,
+        MovementV1::SouthWest,
+        MovementV1::West,
+        MovementV1::NorthWest,
+    ];
+
     pub fn is_diagonal(&self) -> bool {
         matches!(self, Self::NorthEast | Self::SouthEast | Self::SouthWest | Self::NorthWest)
     }
 
+    /// Time a step takes at `speed` onto ground whose step time is `step_time_percent` of the base.
+    pub fn step_duration_ms(&self, speed: u16, step_time_percent: u16) -> u64 {
+        let mut duration_ms = MOVEMENT_COOLDOWN_FACTOR * step_time_percent as u64 / BASE_STEP_TIME_PERCENT as u64;
+        duration_ms /= speed.max(1) as u64;
+        if self.is_diagonal() {
+            duration_ms = duration_ms * 1_414_213 / 1_000_000;
+        }
+        duration_ms
+    }
+
+    /// The two orthogonal neighbours a diagonal step from `from` passes between, `None` for straight steps.
+    pub fn corners(&self, from: Vec2) -> Option<[Vec2; 2]> {
+        let (x, y) = self.translate(from.x, from.y);
+        self.is_diagonal().then_some([Vec2::new(x, from.y), Vec2::new(from.x, y)])
+    }
+
     pub fn translate(&self, x: u16, y: u16) -> (u16, u16) {
         match self {
             MovementV1::North => (x, y.saturating_sub(1)),
@@ -156,15 +292,583 @@ impl MovementV1 {
     }
 }
 
+/// What keeps a diagonal step from cutting the corner between its two orthogonal neighbours.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, SpacetimeType)]
+pub enum CornerRuleV1 {
+    /// Diagonals only check their destination.
+    Free,
+    /// Blocked when either neighbour cannot be walked on.
+    #[default]
+    Terrain,
+    /// Blocked when either neighbour cannot be walked on or has someone standing on it.
+    TerrainAndOccupants,
+}
+
+impl CornerRuleV1 {
+    pub fn allows(
+        &self,
+        from: Vec2,
+        movement: MovementV1,
+        mut is_walkable: impl FnMut(Vec2) -> bool,
+        mut is_occupied: impl FnMut(Vec2) -> bool,
+    ) -> bool {
+        let Some(corners) = movement.corners(from) else {
+            return true;
+        };
+        match self {
+            Self::Free => true,
+            Self::Terrain => corners.into_iter().all(is_walkable),
+            Self::TerrainAndOccupants => corners.into_iter().all(|corner| is_walkable(corner) && !is_occupied(corner)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, SpacetimeType)]
 pub enum MapTileV1 {
     Water,
     Grass,
+    Sand,
+    Dirt,
+    StoneFloor,
+    GrassBorder,
+    SandBorder,
+    Tree,
+    Bush,
+    Stone,
+    Wall,
+    StairUp,
+    Ladder,
+    Hole,
+    Road,
+    Swamp,
+    Snow,
+    /// House door; who may pass is decided by the house access list, not the tile.
+    Door,
+}
+
+/// Rules attached to an area of the map; overlapping regions combine their flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, SpacetimeType)]
+pub struct RegionFlagsV1 {
+    /// No combat of any kind.
+    pub protection_zone: bool,
+    /// Players cannot attack each other, monsters still can.
+    pub no_pvp: bool,
+    /// Characters cannot log out or switch characters here.
+    pub no_logout: bool,
+    pub house: bool,
+}
+
+impl RegionFlagsV1 {
+    pub fn union(self, other: RegionFlagsV1) -> Self {
+        Self {
+            protection_zone: self.protection_zone || other.protection_zone,
+            no_pvp: self.no_pvp || other.no_pvp,
+            no_logout: self.no_logout || other.no_logout,
+            house: self.house || other.house,
+        }
+    }
+
+    /// Flags set in `self` but not in `other`.
+    pub fn difference(self, other: RegionFlagsV1) -> Self {
+        Self {
+            protection_zone: self.protection_zone && !other.protection_zone,
+            no_pvp: self.no_pvp && !other.no_pvp,
+            no_logout: self.no_logout && !other.no_logout,
+            house: self.house && !other.house,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn allows_combat(&self) -> bool {
+        !self.protection_zone
+    }
+
+    pub fn allows_pvp(&self) -> bool {
+        self.allows_combat() && !self.no_pvp
+    }
+
+    pub fn allows_logout(&self) -> bool {
+        !self.no_logout
+    }
+}
+
+/// Direction a floor link takes a character; floors above have a higher `z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SpacetimeType)]
+pub enum FloorTransitionV1 {
+    Up,
+    Down,
+}
+
+impl FloorTransitionV1 {
+    pub fn target_z(&self, z: u8) -> Option<u8> {
+        match self {
+            FloorTransitionV1::Up => z.checked_add(1),
+            FloorTransitionV1::Down => z.checked_sub(1),
+        }
+    }
+}
+
+/// Per-tile properties combined across every layer of a map position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileFlags {
+    /// The tile gives footing, e.g. grass or a bridge.
+    pub walkable: bool,
+    /// The tile stops movement regardless of the footing below.
+    pub blocking: bool,
+    /// The tile hides whatever is behind it.
+    pub sight_blocking: bool,
+}
+
+impl TileFlags {
+    const fn new(walkable: bool, blocking: bool, sight_blocking: bool) -> Self {
+        Self {
+            walkable,
+            blocking,
+            sight_blocking,
+        }
+    }
 }
 
 impl MapTileV1 {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Water" => Some(MapTileV1::Water),
+            "Grass" => Some(MapTileV1::Grass),
+            "Sand" => Some(MapTileV1::Sand),
+            "Dirt" => Some(MapTileV1::Dirt),
+            "StoneFloor" => Some(MapTileV1::StoneFloor),
+            "GrassBorder" => Some(MapTileV1::GrassBorder),
+            "SandBorder" => Some(MapTileV1::SandBorder),
+            "Tree" => Some(MapTileV1::Tree),
+            "Bush" => Some(MapTileV1::Bush),
+            "Stone" => Some(MapTileV1::Stone),
+            "Wall" => Some(MapTileV1::Wall),
+            "StairUp" => Some(MapTileV1::StairUp),
+            "Ladder" => Some(MapTileV1::Ladder),
+            "Hole" => Some(MapTileV1::Hole),
+            "Road" => Some(MapTileV1::Road),
+            "Swamp" => Some(MapTileV1::Swamp),
+            "Snow" => Some(MapTileV1::Snow),
+            "Door" => Some(MapTileV1::Door),
+            _ => None,
+        }
+    }
+
+    /// Ground tiles form the bottom of a stack, everything else is layered on top.
+    pub fn is_ground(&self) -> bool {
+        matches!(
+            self,
+            MapTileV1::Water
+                | MapTileV1::Grass
+                | MapTileV1::Sand
+                | MapTileV1::Dirt
+                | MapTileV1::StoneFloor
+                | MapTileV1::Road
+                | MapTileV1::Swamp
+                | MapTileV1::Snow
+        )
+    }
+
+    pub fn flags(&self) -> TileFlags {
+        match self {
+            MapTileV1::Water => TileFlags::new(false, false, false),
+            MapTileV1::Grass
+            | MapTileV1::Sand
+            | MapTileV1::Dirt
+            | MapTileV1::StoneFloor
+            | MapTileV1::Road
+            | MapTileV1::Swamp
+            | MapTileV1::Snow => TileFlags::new(true, false, false),
+            MapTileV1::GrassBorder | MapTileV1::SandBorder => TileFlags::new(false, false, false),
+            MapTileV1::Bush | MapTileV1::Stone => TileFlags::new(false, true, false),
+            MapTileV1::Tree | MapTileV1::Wall => TileFlags::new(false, true, true),
+            MapTileV1::StairUp | MapTileV1::Ladder | MapTileV1::Hole | MapTileV1::Door => TileFlags::new(false, false, false),
+        }
+    }
+
+    /// Step time on this tile as a percentage of the base step time, so roads are quicker and swamps slower.
+    pub fn step_time_percent(&self) -> Option<u16> {
+        match self {
+            MapTileV1::Road => Some(80),
+            MapTileV1::StoneFloor => Some(90),
+            MapTileV1::Grass | MapTileV1::Dirt => Some(BASE_STEP_TIME_PERCENT),
+            MapTileV1::Sand => Some(115),
+            MapTileV1::Snow => Some(135),
+            MapTileV1::Swamp => Some(160),
+            _ => None,
+        }
+    }
+
+    /// Stairs and ladders are used to climb up, holes drop whoever walks into them.
+    pub fn floor_transition(&self) -> Option<FloorTransitionV1> {
+        match self {
+            MapTileV1::StairUp | MapTileV1::Ladder => Some(FloorTransitionV1::Up),
+            MapTileV1::Hole => Some(FloorTransitionV1::Down),
+            _ => None,
+        }
+    }
+}
+
+/// A ground tile plus the layers stacked on top of it, ordered bottom to top.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SpacetimeType)]
+pub struct MapTileStackV1 {
+    pub ground: MapTileV1,
+    pub layers: Vec<MapTileV1>,
+}
+
+impl MapTileStackV1 {
+    pub fn new(ground: MapTileV1) -> Self {
+        Self {
+            ground,
+            layers: Vec::new(),
+        }
+    }
+
+    pub fn with_layer(mut self, layer: MapTileV1) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = MapTileV1> + '_ {
+        std::iter::once(self.ground).chain(self.layers.iter().copied())
+    }
+
+    /// Walkable when any tile gives footing and none of them blocks movement.
     pub fn is_walkable(&self) -> bool {
-        matches!(self, MapTileV1::Grass)
+        self.tiles().any(|tile| tile.flags().walkable) && !self.tiles().any(|tile| tile.flags().blocking)
+    }
+
+    pub fn is_sight_blocking(&self) -> bool {
+        self.tiles().any(|tile| tile.flags().sight_blocking)
+    }
+
+    /// Step time of the topmost tile that sets one, falling back to the base step time.
+    pub fn step_time_percent(&self) -> u16 {
+        self.layers
+            .iter()
+            .rev()
+            .chain(std::iter::once(&self.ground))
+            .find_map(MapTileV1::step_time_percent)
+            .unwrap_or(BASE_STEP_TIME_PERCENT)
+    }
+
+    /// Transition of the topmost stair, ladder or hole in the stack.
+    pub fn floor_transition(&self) -> Option<FloorTransitionV1> {
+        self.layers.iter().rev().find_map(MapTileV1::floor_transition)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, SpacetimeType)]
+pub enum PvpModeV1 {
+    /// Players can only fight each other by mutual agreement.
+    Optional,
+    Open,
+    Hardcore,
+}
+
+impl PvpModeV1 {
+    /// Region flags as the world's PvP mode enforces them: optional PvP makes every area `no_pvp` until fights can be
+    /// agreed on, open PvP keeps the map's regions and hardcore only leaves protection zones safe.
+    pub fn apply(&self, flags: RegionFlagsV1) -> RegionFlagsV1 {
+        match self {
+            Self::Optional => RegionFlagsV1 { no_pvp: true, ..flags },
+            Self::Open => flags,
+            Self::Hardcore => RegionFlagsV1 { no_pvp: false, ..flags },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SpacetimeType)]
+pub struct WorldFeaturesV1 {
+    pub chat: bool,
+}
+
+impl Default for WorldFeaturesV1 {
+    fn default() -> Self {
+        Self { chat: true }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapChunk {
+    pub rect: Rect,
+    pub z: u8,
+    pub tiles: MapTileStackV1,
+}
+
+/// Map content produced by an importer or generator, turned into table rows by the methods below.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MapBlueprint {
+    pub chunks: Vec<MapChunk>,
+    pub temples: Vec<TempleSeed>,
+    pub regions: Vec<RegionSeed>,
+}
+
+impl MapBlueprint {
+    /// `map_v1` rows of every chunk, split by sector and merged within each; later chunks overwrite earlier ones.
+    pub fn map_rows(&self) -> Vec<MapV1> {
+        let mut sectors: BTreeMap<(u64, u8), Vec<_>> = BTreeMap::new();
+        for chunk in &self.chunks {
+            for piece in chunk.rect.split_by_sector() {
+                let key = (piece.min.with_z(chunk.z).sector_key(), chunk.z);
+                sectors.entry(key).or_default().push((piece, chunk.tiles.clone()));
+            }
+        }
+
+        let mut rows = Vec::new();
+        for ((sector_key, z), rects) in sectors {
+            let Some(grid) = TileGrid::from_rects(&rects) else {
+                continue;
+            };
+            rows.extend(grid.merge_rects().into_iter().map(|(rect, tiles)| MapV1 {
+                map_id: rect.min.with_z(z).map_id(),
+                sector_key,
+                x1: rect.min.x,
+                y1: rect.min.y,
+                x2: rect.max.x,
+                y2: rect.max.y,
+                z,
+                tiles,
+            }));
+        }
+        rows
+    }
+
+    /// Floor links of every stair, ladder and hole; a later chunk's link replaces an earlier one on the same tile.
+    pub fn floor_links(&self) -> Vec<FloorLinkV1> {
+        let mut links = BTreeMap::new();
+        for chunk in &self.chunks {
+            for link in floor_links_in(chunk.rect, chunk.z, &chunk.tiles) {
+                links.insert(link.map_id, link);
+            }
+        }
+        links.into_values().collect()
+    }
+
+    /// `map_region_v1` rows of every region, split by sector.
+    pub fn region_rows(&self) -> Vec<MapRegionV1> {
+        self.regions
+            .iter()
+            .flat_map(|region| region_rows_in(region.rect, region.z, region.flags))
+            .collect()
+    }
+}
+
+/// Links for every tile of `rect` when its tiles lead to another floor, pointing straight up or down.
+pub fn floor_links_in(rect: Rect, z: u8, tiles: &MapTileStackV1) -> Vec<FloorLinkV1> {
+    let Some(transition) = tiles.floor_transition() else {
+        return Vec::new();
+    };
+    let Some(target_z) = transition.target_z(z) else {
+        return Vec::new();
+    };
+
+    let mut links = Vec::new();
+    for x in rect.min.x..=rect.max.x {
+        for y in rect.min.y..=rect.max.y {
+            let source = Vec3::new(x, y, z);
+            links.push(FloorLinkV1 {
+                map_id: source.map_id(),
+                sector_key: source.sector_key(),
+                transition,
+                target_x: x,
+                target_y: y,
+                target_z,
+            });
+        }
+    }
+    links
+}
+
+/// Region rows covering `rect`, one per sector it crosses; the ids are left for `auto_inc`.
+pub fn region_rows_in(rect: Rect, z: u8, flags: RegionFlagsV1) -> Vec<MapRegionV1> {
+    rect.split_by_sector()
+        .into_iter()
+        .map(|piece| MapRegionV1 {
+            map_region_id: 0,
+            sector_key: piece.min.with_z(z).sector_key(),
+            x1: piece.min.x,
+            y1: piece.min.y,
+            x2: piece.max.x,
+            y2: piece.max.y,
+            z,
+            flags,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionSeed {
+    pub rect: Rect,
+    pub z: u8,
+    pub flags: RegionFlagsV1,
+}
+
+/// Town temple shipped with a map, matched to existing temples by name when the map is applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TempleSeed {
+    pub name: String,
+    pub position: Vec3,
+}
+
+/// Cached chunks to drop before one more fits in `cache_size`, least recently walked first.
+pub fn walked_chunks_to_evict<'a>(cached: impl IntoIterator<Item = &'a WalkedMapChunkV1>, cache_size: usize) -> Vec<u64> {
+    let mut cached: Vec<_> = cached.into_iter().collect();
+    cached.sort_by_key(|cache| (cache.last_walked_at, cache.walked_map_chunk_id));
+    let overflow = (cached.len() + 1).saturating_sub(cache_size);
+    cached
+        .into_iter()
+        .take(overflow)
+        .map(|cache| cache.walked_map_chunk_id)
+        .collect()
+}
+
+/// Cached chunks that overlap an edited `rect` on floor `z` and may no longer match the map.
+pub fn stale_walked_chunks<'a>(cached: impl IntoIterator<Item = &'a WalkedMapChunkV1>, rect: Rect, z: u8) -> Vec<u64> {
+    cached
+        .into_iter()
+        .filter(|cache| cache.z == z && Rect::from(*cache).overlaps(&rect))
+        .map(|cache| cache.walked_map_chunk_id)
+        .collect()
+}
+
+/// Characters standing where `is_standable` says nobody can stand, e.g. after the ground under them was erased or
+/// painted over with a wall.
+pub fn stranded_characters<'a>(
+    positions: impl IntoIterator<Item = &'a CharacterPositionV1>,
+    is_standable: impl Fn(Vec3) -> bool,
+) -> Vec<u64> {
+    positions
+        .into_iter()
+        .filter(|position| !is_standable(Vec3::new(position.x, position.y, position.z)))
+        .map(|position| position.character_id)
+        .collect()
+}
+
+/// Result of a single step: either the character moved and ended up at the position, or the tile it tried to step
+/// onto was blocked by terrain, an occupant, a corner or a locked door, which only turns it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    Moved(Vec3),
+    Blocked,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MapCompactionReport {
+    pub rows_before: u64,
+    pub rows_after: u64,
+}
+
+/// Dense grid of optional cells anchored at `origin`, used to turn per-tile data into rects.
+#[derive(Debug, Clone)]
+pub struct TileGrid<T> {
+    origin: Vec2,
+    width: usize,
+    height: usize,
+    cells: Vec<Option<T>>,
+}
+
+impl<T: Clone + PartialEq> TileGrid<T> {
+    pub fn new(origin: Vec2, width: usize, height: usize) -> Self {
+        Self {
+            origin,
+            width,
+            height,
+            cells: vec![None; width * height],
+        }
+    }
+
+    /// Rasterizes rects into a grid spanning their bounding box; later rects overwrite earlier ones.
+    pub fn from_rects(rects: &[(Rect, T)]) -> Option<Self> {
+        let min_x = rects.iter().map(|(rect, _)| rect.min.x).min()?;
+        let min_y = rects.iter().map(|(rect, _)| rect.min.y).min()?;
+        let max_x = rects.iter().map(|(rect, _)| rect.max.x).max()?;
+        let max_y = rects.iter().map(|(rect, _)| rect.max.y).max()?;
+
+        let mut grid = Self::new(
+            Vec2::new(min_x, min_y),
+            (max_x - min_x) as usize + 1,
+            (max_y - min_y) as usize + 1,
+        );
+        for (rect, value) in rects {
+            for y in rect.min.y..=rect.max.y {
+                for x in rect.min.x..=rect.max.x {
+                    grid.set(Vec2::new(x, y), value.clone());
+                }
+            }
+        }
+        Some(grid)
+    }
+
+    pub fn set(&mut self, point: Vec2, value: T) -> bool {
+        let Some(index) = self.index_of(point) else {
+            return false;
+        };
+        self.cells[index] = Some(value);
+        true
+    }
+
+    pub fn get(&self, point: Vec2) -> Option<&T> {
+        self.cells[self.index_of(point)?].as_ref()
+    }
+
+    /// Greedily merges equal neighbouring cells into rects, scanning row by row.
+    /// Each rect grows to the right first and then down while the whole row still matches.
+    pub fn merge_rects(&self) -> Vec<(Rect, T)> {
+        let mut visited = vec![false; self.cells.len()];
+        let mut rects = Vec::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let start = y * self.width + x;
+                if visited[start] {
+                    continue;
+                }
+                let Some(value) = &self.cells[start] else {
+                    continue;
+                };
+
+                let matches = |index: usize, visited: &[bool]| !visited[index] && self.cells[index].as_ref() == Some(value);
+
+                let mut width = 1;
+                while x + width < self.width && matches(start + width, &visited) {
+                    width += 1;
+                }
+
+                let mut height = 1;
+                while y + height < self.height {
+                    let row = (y + height) * self.width + x;
+                    if !(row..row + width).all(|index| matches(index, &visited)) {
+                        break;
+                    }
+                    height += 1;
+                }
+
+                for row in y..y + height {
+                    let offset = row * self.width + x;
+                    visited[offset..offset + width].fill(true);
+                }
+
+                let x1 = self.origin.x + x as u16;
+                let y1 = self.origin.y + y as u16;
+                let rect = Rect::new(x1, y1, x1 + (width - 1) as u16, y1 + (height - 1) as u16);
+                rects.push((rect, value.clone()));
+            }
+        }
+
+        rects
+    }
+
+    fn index_of(&self, point: Vec2) -> Option<usize> {
+        let x = point.x.checked_sub(self.origin.x)? as usize;
+        let y = point.y.checked_sub(self.origin.y)? as usize;
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(y * self.width + x)
     }
 }
 
@@ -172,6 +876,54 @@ impl MapTileV1 {
 mod tests {
     use super::*;
 
+    #[test]
+    fn blueprint_rows_follow_sectors_and_later_chunks_win() {
+        let grass = MapTileStackV1::new(MapTileV1::Grass);
+        let blueprint = MapBlueprint {
+            chunks: vec![
+                MapChunk {
+                    rect: Rect::new(SECTOR_SIZE - 2, 0, SECTOR_SIZE + 1, 1),
+                    z: 7,
+                    tiles: grass.clone().with_layer(MapTileV1::Ladder),
+                },
+                MapChunk {
+                    rect: Rect::new(SECTOR_SIZE, 0, SECTOR_SIZE + 1, 1),
+                    z: 7,
+                    tiles: grass.clone().with_layer(MapTileV1::Hole),
+                },
+            ],
+            regions: vec![RegionSeed {
+                rect: Rect::new(SECTOR_SIZE - 1, 0, SECTOR_SIZE, 0),
+                z: 7,
+                flags: RegionFlagsV1 {
+                    no_logout: true,
+                    ..RegionFlagsV1::default()
+                },
+            }],
+            ..MapBlueprint::default()
+        };
+
+        let rows = blueprint.map_rows();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| Rect::from(row).split_by_sector().len() == 1));
+        assert!(
+            rows.iter()
+                .any(|row| row.x1 == SECTOR_SIZE && row.tiles == grass.clone().with_layer(MapTileV1::Hole))
+        );
+
+        let links = blueprint.floor_links();
+        assert_eq!(links.len(), 8);
+        assert!(
+            links
+                .iter()
+                .all(|link| link.target_z == if link.target_x < SECTOR_SIZE { 8 } else { 6 })
+        );
+
+        let regions = blueprint.region_rows();
+        assert_eq!(regions.len(), 2);
+        assert_ne!(regions[0].sector_key, regions[1].sector_key);
+    }
+
     #[test]
     fn map_id_roundtrip() {
         let pos = Vec3::new(1152, 1152, 127);
@@ -208,6 +960,15 @@ mod tests {
         assert_ne!(a, b);
     }
 
+    #[test]
+    fn rect_around_clamps_to_world_edges() {
+        assert_eq!(Rect::around(Vec2::new(10, 10), 2), Rect::new(8, 8, 12, 12));
+        assert_eq!(
+            Rect::around(Vec2::new(1, u16::MAX), 2),
+            Rect::new(0, u16::MAX - 2, 3, u16::MAX)
+        );
+    }
+
     #[test]
     fn rect_contains_inside() {
         assert!(Rect::new(0, 0, 100, 100).contains(Vec2::new(50, 50)));
@@ -246,4 +1007,356 @@ mod tests {
     fn rect_overlaps_contained() {
         assert!(Rect::new(0, 0, 100, 100).overlaps(&Rect::new(25, 25, 75, 75)));
     }
+
+    #[test]
+    fn rect_intersection_clips_to_overlap() {
+        assert_eq!(
+            Rect::new(0, 0, 50, 50).intersection(&Rect::new(25, 40, 75, 75)),
+            Some(Rect::new(25, 40, 50, 50))
+        );
+        assert_eq!(Rect::new(0, 0, 50, 50).intersection(&Rect::new(51, 0, 75, 75)), None);
+    }
+
+    #[test]
+    fn rect_subtract_hole_leaves_four_pieces() {
+        let pieces = Rect::new(0, 0, 9, 9).subtract(&Rect::new(3, 4, 5, 6));
+        assert_eq!(
+            pieces,
+            vec![
+                Rect::new(0, 0, 9, 3),
+                Rect::new(0, 7, 9, 9),
+                Rect::new(0, 4, 2, 6),
+                Rect::new(6, 4, 9, 6),
+            ]
+        );
+        assert_eq!(pieces.iter().map(Rect::area).sum::<u32>(), 100 - 9);
+    }
+
+    #[test]
+    fn rect_subtract_covering_rect_leaves_nothing() {
+        assert!(Rect::new(2, 2, 4, 4).subtract(&Rect::new(0, 0, 9, 9)).is_empty());
+        assert_eq!(
+            Rect::new(2, 2, 4, 4).subtract(&Rect::new(5, 5, 9, 9)),
+            vec![Rect::new(2, 2, 4, 4)]
+        );
+    }
+
+    #[test]
+    fn rect_split_by_sector_follows_sector_borders() {
+        assert_eq!(Rect::new(10, 10, 20, 20).split_by_sector(), vec![Rect::new(10, 10, 20, 20)]);
+        assert_eq!(
+            Rect::new(250, 500, 260, 520).split_by_sector(),
+            vec![
+                Rect::new(250, 500, 255, 511),
+                Rect::new(250, 512, 255, 520),
+                Rect::new(256, 500, 260, 511),
+                Rect::new(256, 512, 260, 520),
+            ]
+        );
+        assert_eq!(
+            Rect::new(u16::MAX - 1, 0, u16::MAX, 0).split_by_sector(),
+            vec![Rect::new(u16::MAX - 1, 0, u16::MAX, 0)]
+        );
+    }
+
+    #[test]
+    fn region_flags_combine_and_gate_combat() {
+        let pz = RegionFlagsV1 {
+            protection_zone: true,
+            ..Default::default()
+        };
+        let no_pvp = RegionFlagsV1 {
+            no_pvp: true,
+            ..Default::default()
+        };
+
+        assert!(RegionFlagsV1::default().is_empty());
+        assert!(RegionFlagsV1::default().allows_pvp());
+        assert!(!pz.allows_combat() && !pz.allows_pvp());
+        assert!(no_pvp.allows_combat() && !no_pvp.allows_pvp());
+        assert_eq!(
+            pz.union(no_pvp),
+            RegionFlagsV1 {
+                protection_zone: true,
+                no_pvp: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(pz.union(no_pvp).difference(pz), no_pvp);
+        assert!(no_pvp.difference(no_pvp).is_empty());
+    }
+
+    #[test]
+    fn pvp_mode_overrides_only_the_no_pvp_flag() {
+        let arena = RegionFlagsV1::default();
+        let town = RegionFlagsV1 {
+            no_pvp: true,
+            ..Default::default()
+        };
+        let temple = RegionFlagsV1 {
+            protection_zone: true,
+            ..Default::default()
+        };
+
+        assert!(!PvpModeV1::Optional.apply(arena).allows_pvp());
+        assert!(PvpModeV1::Open.apply(arena).allows_pvp());
+        assert!(!PvpModeV1::Open.apply(town).allows_pvp());
+        assert!(PvpModeV1::Hardcore.apply(town).allows_pvp());
+        assert!(!PvpModeV1::Hardcore.apply(temple).allows_pvp());
+    }
+
+    #[test]
+    fn stranded_characters_are_the_ones_off_standable_ground() {
+        let position = |character_id: u64, x: u16| CharacterPositionV1 {
+            character_id,
+            x,
+            y: 10,
+            z: 7,
+            movement: MovementV1::default(),
+            direction: DirectionV1::default(),
+            arrives_at: spacetimedb::Timestamp::UNIX_EPOCH,
+        };
+        let positions = [position(1, 10), position(2, 20), position(3, 30)];
+        let erased = Rect::new(15, 0, 25, 20);
+        let walled = Vec3::new(30, 10, 7);
+
+        let stranded = stranded_characters(&positions, |pos| !erased.contains(pos.into()) && pos != walled);
+
+        assert_eq!(stranded, vec![2, 3]);
+    }
+
+    fn walked_chunk(walked_map_chunk_id: u64, rect: Rect, z: u8, last_walked_micros: i64) -> WalkedMapChunkV1 {
+        WalkedMapChunkV1 {
+            walked_map_chunk_id,
+            character_id: 1,
+            map_id: rect.min.with_z(z).map_id(),
+            x1: rect.min.x,
+            y1: rect.min.y,
+            x2: rect.max.x,
+            y2: rect.max.y,
+            z,
+            step_time_percent: BASE_STEP_TIME_PERCENT,
+            last_walked_at: spacetimedb::Timestamp::from_micros_since_unix_epoch(last_walked_micros),
+        }
+    }
+
+    #[test]
+    fn walked_chunks_are_evicted_by_last_use() {
+        let cached = [
+            walked_chunk(1, Rect::new(0, 0, 9, 9), 7, 300),
+            walked_chunk(2, Rect::new(10, 0, 19, 9), 7, 100),
+            walked_chunk(3, Rect::new(20, 0, 29, 9), 7, 200),
+            walked_chunk(4, Rect::new(30, 0, 39, 9), 7, 100),
+        ];
+
+        assert_eq!(walked_chunks_to_evict(&cached, 5), Vec::<u64>::new());
+        assert_eq!(walked_chunks_to_evict(&cached, 4), vec![2]);
+        assert_eq!(walked_chunks_to_evict(&cached, 2), vec![2, 4, 3]);
+    }
+
+    #[test]
+    fn map_edits_invalidate_overlapping_walked_chunks_on_their_floor() {
+        let cached = [
+            walked_chunk(1, Rect::new(0, 0, 9, 9), 7, 0),
+            walked_chunk(2, Rect::new(10, 0, 19, 9), 7, 0),
+            walked_chunk(3, Rect::new(5, 5, 14, 14), 8, 0),
+        ];
+
+        assert_eq!(stale_walked_chunks(&cached, Rect::new(9, 9, 10, 9), 7), vec![1, 2]);
+        assert_eq!(stale_walked_chunks(&cached, Rect::new(15, 0, 15, 0), 7), vec![2]);
+        assert_eq!(stale_walked_chunks(&cached, Rect::new(20, 20, 30, 30), 7), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn tile_stack_floor_transition_uses_topmost_layer() {
+        assert_eq!(MapTileStackV1::new(MapTileV1::Grass).floor_transition(), None);
+        assert_eq!(
+            MapTileStackV1::new(MapTileV1::StoneFloor)
+                .with_layer(MapTileV1::Hole)
+                .with_layer(MapTileV1::Ladder)
+                .floor_transition(),
+            Some(FloorTransitionV1::Up)
+        );
+        assert!(
+            MapTileStackV1::new(MapTileV1::Grass)
+                .with_layer(MapTileV1::StairUp)
+                .is_walkable()
+        );
+    }
+
+    #[test]
+    fn tile_stack_step_time_uses_topmost_tile_that_sets_one() {
+        assert_eq!(MapTileStackV1::new(MapTileV1::Swamp).step_time_percent(), 160);
+        assert_eq!(
+            MapTileStackV1::new(MapTileV1::Road)
+                .with_layer(MapTileV1::StairUp)
+                .step_time_percent(),
+            80
+        );
+        assert_eq!(
+            MapTileStackV1::new(MapTileV1::Water).step_time_percent(),
+            BASE_STEP_TIME_PERCENT
+        );
+    }
+
+    #[test]
+    fn step_duration_scales_with_ground_and_diagonal() {
+        assert_eq!(MovementV1::North.step_duration_ms(200, BASE_STEP_TIME_PERCENT), 200);
+        assert_eq!(MovementV1::North.step_duration_ms(200, 80), 160);
+        assert_eq!(MovementV1::South.step_duration_ms(200, 160), 320);
+        assert_eq!(MovementV1::NorthEast.step_duration_ms(200, BASE_STEP_TIME_PERCENT), 282);
+        assert_eq!(MovementV1::West.step_duration_ms(0, BASE_STEP_TIME_PERCENT), 40_000);
+    }
+
+    #[test]
+    fn floor_transition_target_z_stops_at_world_limits() {
+        assert_eq!(FloorTransitionV1::Up.target_z(127), Some(128));
+        assert_eq!(FloorTransitionV1::Down.target_z(127), Some(126));
+        assert_eq!(FloorTransitionV1::Up.target_z(u8::MAX), None);
+        assert_eq!(FloorTransitionV1::Down.target_z(0), None);
+    }
+
+    #[test]
+    fn tile_stack_walkable_needs_footing() {
+        assert!(MapTileStackV1::new(MapTileV1::Grass).is_walkable());
+        assert!(!MapTileStackV1::new(MapTileV1::Water).is_walkable());
+        assert!(
+            !MapTileStackV1::new(MapTileV1::Water)
+                .with_layer(MapTileV1::SandBorder)
+                .is_walkable()
+        );
+    }
+
+    #[test]
+    fn tile_stack_any_blocking_layer_blocks() {
+        let stack = MapTileStackV1::new(MapTileV1::Grass)
+            .with_layer(MapTileV1::GrassBorder)
+            .with_layer(MapTileV1::Stone);
+        assert!(!stack.is_walkable());
+        assert!(!stack.is_sight_blocking());
+    }
+
+    #[test]
+    fn tile_stack_sight_blocking_combines_layers() {
+        assert!(
+            MapTileStackV1::new(MapTileV1::Grass)
+                .with_layer(MapTileV1::Wall)
+                .is_sight_blocking()
+        );
+        assert!(
+            !MapTileStackV1::new(MapTileV1::Grass)
+                .with_layer(MapTileV1::Bush)
+                .is_sight_blocking()
+        );
+    }
+
+    #[test]
+    fn tile_grid_merges_uniform_area_into_single_rect() {
+        let mut grid = TileGrid::new(Vec2::new(10, 20), 4, 3);
+        for x in 10..14 {
+            for y in 20..23 {
+                grid.set(Vec2::new(x, y), MapTileV1::Grass);
+            }
+        }
+        assert_eq!(grid.merge_rects(), vec![(Rect::new(10, 20, 13, 22), MapTileV1::Grass)]);
+    }
+
+    #[test]
+    fn tile_grid_keeps_different_tiles_apart() {
+        let mut grid = TileGrid::new(Vec2::new(0, 0), 3, 2);
+        for x in 0..3 {
+            grid.set(Vec2::new(x, 0), MapTileV1::Water);
+            grid.set(Vec2::new(x, 1), MapTileV1::Grass);
+        }
+        grid.set(Vec2::new(2, 1), MapTileV1::Water);
+
+        assert_eq!(
+            grid.merge_rects(),
+            vec![
+                (Rect::new(0, 0, 2, 0), MapTileV1::Water),
+                (Rect::new(0, 1, 1, 1), MapTileV1::Grass),
+                (Rect::new(2, 1, 2, 1), MapTileV1::Water),
+            ]
+        );
+    }
+
+    #[test]
+    fn tile_grid_from_rects_merges_fragmented_rects() {
+        let fragments: Vec<_> = (0..4)
+            .flat_map(|x| (0..4).map(move |y| (Rect::new(x, y, x, y), MapTileV1::Grass)))
+            .chain([(Rect::new(4, 0, 4, 1), MapTileV1::Water)])
+            .collect();
+
+        let merged = TileGrid::from_rects(&fragments).unwrap().merge_rects();
+
+        assert_eq!(
+            merged,
+            vec![
+                (Rect::new(0, 0, 3, 3), MapTileV1::Grass),
+                (Rect::new(4, 0, 4, 1), MapTileV1::Water),
+            ]
+        );
+    }
+
+    #[test]
+    fn corners_are_the_orthogonal_neighbours_of_each_diagonal() {
+        let from = Vec2::new(10, 10);
+
+        assert_eq!(
+            MovementV1::NorthEast.corners(from),
+            Some([Vec2::new(11, 10), Vec2::new(10, 9)])
+        );
+        assert_eq!(
+            MovementV1::SouthEast.corners(from),
+            Some([Vec2::new(11, 10), Vec2::new(10, 11)])
+        );
+        assert_eq!(
+            MovementV1::SouthWest.corners(from),
+            Some([Vec2::new(9, 10), Vec2::new(10, 11)])
+        );
+        assert_eq!(
+            MovementV1::NorthWest.corners(from),
+            Some([Vec2::new(9, 10), Vec2::new(10, 9)])
+        );
+        assert_eq!(MovementV1::North.corners(from), None);
+        assert_eq!(MovementV1::East.corners(from), None);
+    }
+
+    #[test]
+    fn terrain_corner_rule_blocks_diagonals_past_any_blocked_neighbour() {
+        let from = Vec2::new(10, 10);
+        let wall_east = |pos: Vec2| pos != Vec2::new(11, 10);
+        let nobody = |_| false;
+
+        assert!(!CornerRuleV1::Terrain.allows(from, MovementV1::NorthEast, wall_east, nobody));
+        assert!(!CornerRuleV1::Terrain.allows(from, MovementV1::SouthEast, wall_east, nobody));
+        assert!(CornerRuleV1::Terrain.allows(from, MovementV1::SouthWest, wall_east, nobody));
+        assert!(CornerRuleV1::Terrain.allows(from, MovementV1::NorthWest, wall_east, nobody));
+        assert!(CornerRuleV1::Terrain.allows(from, MovementV1::East, |_| false, nobody));
+        assert!(CornerRuleV1::Free.allows(from, MovementV1::NorthEast, wall_east, nobody));
+    }
+
+    #[test]
+    fn occupant_corner_rule_also_blocks_diagonals_past_characters() {
+        let from = Vec2::new(10, 10);
+        let open = |_| true;
+        let someone_west = |pos: Vec2| pos == Vec2::new(9, 10);
+
+        for movement in [MovementV1::SouthWest, MovementV1::NorthWest] {
+            assert!(!CornerRuleV1::TerrainAndOccupants.allows(from, movement, open, someone_west));
+            assert!(CornerRuleV1::Terrain.allows(from, movement, open, someone_west));
+        }
+        for movement in [MovementV1::NorthEast, MovementV1::SouthEast] {
+            assert!(CornerRuleV1::TerrainAndOccupants.allows(from, movement, open, someone_west));
+        }
+    }
+
+    #[test]
+    fn tile_grid_skips_empty_cells_and_ignores_out_of_bounds() {
+        let mut grid = TileGrid::new(Vec2::new(5, 5), 2, 2);
+        assert!(grid.set(Vec2::new(6, 6), MapTileV1::Grass));
+        assert!(!grid.set(Vec2::new(7, 6), MapTileV1::Grass));
+        assert!(!grid.set(Vec2::new(4, 5), MapTileV1::Grass));
+        assert_eq!(grid.merge_rects(), vec![(Rect::new(6, 6, 6, 6), MapTileV1::Grass)]);
+    }
 }
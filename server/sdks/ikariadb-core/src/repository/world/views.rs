@@ -1,39 +1,29 @@
 use crate::{
-    constants::SECTOR_SIZE,
-    extend::proximity::{find_ranges, iter_nearby_occupied},
+    extend::proximity::{find_map_chunks_in, find_visibility, iter_nearby_occupied},
     repository::{
         character::{CharacterV1, character_v1__view, online_character_v1__view},
-        world::{CharacterPositionV1, MapV1, map_v1__view, online_character_position_v1__view, types::Rect},
+        world::{
+            CharacterPositionV1, CharacterRegionV1, ExploredSectorV1, MapV1, TownTempleV1, character_region_v1__view,
+            citizenship_v1__view, explored_sector_v1__view, online_character_position_v1__view, town_temple_v1__view,
+            types::Rect,
+        },
     },
 };
 use spacetimedb::{ViewContext, view};
 
 #[view(accessor = vw_world_map_v1, public)]
 pub fn vw_world_map_v1(ctx: &ViewContext) -> Vec<MapV1> {
-    let Some((rect, (min_z, max_z))) = find_ranges(ctx) else {
+    let Some((visibility, mut chunks)) = find_visibility(ctx) else {
         return Vec::new();
     };
 
-    let mut chunks = Vec::new();
-
-    let sec_min_x = rect.min.x / SECTOR_SIZE;
-    let sec_max_x = rect.max.x / SECTOR_SIZE;
-    let sec_min_y = rect.min.y / SECTOR_SIZE;
-    let sec_max_y = rect.max.y / SECTOR_SIZE;
-
-    for z in min_z..=max_z {
-        for sx in sec_min_x..=sec_max_x {
-            for sy in sec_min_y..=sec_max_y {
-                let sector_key = ((z as u64) << 32) | ((sx as u64) << 16) | (sy as u64);
-                for chunk in ctx.db.map_v1().sector_key().filter(sector_key) {
-                    if chunk.z == z && Rect::from(&chunk).overlaps(&rect) {
-                        chunks.push(chunk);
-                    }
-                }
-            }
-        }
+    // The floor below is only sent where it shows through a hole or an uncovered tile.
+    if let Some(below) = visibility.viewer().z.checked_sub(1) {
+        let revealed = find_map_chunks_in(ctx, visibility.rect(), below)
+            .into_iter()
+            .filter(|chunk| visibility.reveals_below(Rect::from(chunk)));
+        chunks.extend(revealed);
     }
-
     chunks
 }
 
@@ -71,3 +61,34 @@ pub fn vw_nearby_character_positions_v1(ctx: &ViewContext) -> Vec<CharacterPosit
     }
     positions
 }
+
+#[view(accessor = vw_world_town_temples_v1, public)]
+pub fn vw_world_town_temples_v1(ctx: &ViewContext) -> Vec<TownTempleV1> {
+    // Views cannot scan a whole table, so every temple is listed through the floor index.
+    ctx.db.town_temple_v1().z().filter(u8::MIN..=u8::MAX).collect()
+}
+
+#[view(accessor = vw_world_my_home_temple_v1, public)]
+pub fn vw_world_my_home_temple_v1(ctx: &ViewContext) -> Option<TownTempleV1> {
+    let current = ctx.db.online_character_v1().user_id().find(ctx.sender())?;
+    let citizenship = ctx.db.citizenship_v1().character_id().find(current.character_id)?;
+    ctx.db.town_temple_v1().town_temple_id().find(citizenship.town_temple_id)
+}
+
+#[view(accessor = vw_world_my_region_v1, public)]
+pub fn vw_world_my_region_v1(ctx: &ViewContext) -> Option<CharacterRegionV1> {
+    let current = ctx.db.online_character_v1().user_id().find(ctx.sender())?;
+    ctx.db.character_region_v1().character_id().find(current.character_id)
+}
+
+#[view(accessor = vw_minimap_explored_v1, public)]
+pub fn vw_minimap_explored_v1(ctx: &ViewContext) -> Vec<ExploredSectorV1> {
+    let Some(current) = ctx.db.online_character_v1().user_id().find(ctx.sender()) else {
+        return Vec::new();
+    };
+    ctx.db
+        .explored_sector_v1()
+        .character_id()
+        .filter(current.character_id)
+        .collect()
+}
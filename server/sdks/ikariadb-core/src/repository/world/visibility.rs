@@ -0,0 +1,214 @@
+use crate::repository::world::types::{FloorTransitionV1, MapTileStackV1, Rect, Vec2, Vec3};
+use ikaria_shared::constants::GROUND_LEVEL;
+use std::ops::RangeInclusive;
+
+/// What a character can see around itself: its own floor in line of sight, the floor right below only through
+/// openings, and never the floors above.
+#[derive(Debug, Clone)]
+pub struct VisibilityGrid {
+    viewer: Vec3,
+    rect: Rect,
+    sight_radius: u16,
+    width: usize,
+    covered: Vec<bool>,
+    sight_blocking: Vec<bool>,
+}
+
+impl VisibilityGrid {
+    pub fn new(viewer: Vec3, radius: u16) -> Self {
+        let rect = Rect::around(viewer.into(), radius);
+        let cells = rect.area() as usize;
+        // Tiles without ground open the view below only above ground level; down there they are just void.
+        let void_is_covered = viewer.z <= GROUND_LEVEL;
+        Self {
+            viewer,
+            rect,
+            sight_radius: radius,
+            width: (rect.max.x - rect.min.x) as usize + 1,
+            covered: vec![void_is_covered; cells],
+            sight_blocking: vec![false; cells],
+        }
+    }
+
+    pub fn viewer(&self) -> Vec3 {
+        self.viewer
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Narrows how far away entities can be seen, e.g. in fog; the map itself is still sent up to the full radius.
+    pub fn with_sight_radius(mut self, sight_radius: u16) -> Self {
+        self.sight_radius = sight_radius;
+        self
+    }
+
+    pub fn floors(&self) -> RangeInclusive<u8> {
+        self.viewer.z.saturating_sub(1)..=self.viewer.z
+    }
+
+    /// Records a map chunk of the viewer's floor. Ground covers the floor below unless a hole opens it up.
+    pub fn add_tiles(&mut self, rect: Rect, z: u8, tiles: &MapTileStackV1) {
+        if z != self.viewer.z {
+            return;
+        }
+        let Some(cut) = rect.intersection(&self.rect) else {
+            return;
+        };
+
+        let covered = tiles.floor_transition() != Some(FloorTransitionV1::Down);
+        let sight_blocking = tiles.is_sight_blocking();
+        for y in cut.min.y..=cut.max.y {
+            for x in cut.min.x..=cut.max.x {
+                let index = self.index(Vec2::new(x, y));
+                self.covered[index] = covered;
+                self.sight_blocking[index] = sight_blocking;
+            }
+        }
+    }
+
+    pub fn can_see(&self, target: Vec3) -> bool {
+        if !Rect::around(self.viewer.into(), self.sight_radius).contains(target.into()) {
+            return false;
+        }
+        if target.z == self.viewer.z {
+            self.has_line_of_sight(target.into())
+        } else if target.z.checked_add(1) == Some(self.viewer.z) {
+            self.is_opening(target.into())
+        } else {
+            false
+        }
+    }
+
+    /// Whether any part of a chunk on the floor below shows through an opening of the viewer's floor.
+    pub fn reveals_below(&self, rect: Rect) -> bool {
+        let Some(cut) = rect.intersection(&self.rect) else {
+            return false;
+        };
+        (cut.min.y..=cut.max.y).any(|y| (cut.min.x..=cut.max.x).any(|x| self.is_opening(Vec2::new(x, y))))
+    }
+
+    pub fn is_opening(&self, point: Vec2) -> bool {
+        self.rect.contains(point) && !self.covered[self.index(point)]
+    }
+
+    /// Walks a Bresenham line from the viewer; any sight-blocking tile strictly between the two ends hides the target.
+    pub fn has_line_of_sight(&self, target: Vec2) -> bool {
+        let (mut x, mut y) = (self.viewer.x as i32, self.viewer.y as i32);
+        let (target_x, target_y) = (target.x as i32, target.y as i32);
+        let dx = (target_x - x).abs();
+        let dy = -(target_y - y).abs();
+        let step_x = if x < target_x { 1 } else { -1 };
+        let step_y = if y < target_y { 1 } else { -1 };
+        let mut error = dx + dy;
+
+        loop {
+            if x == target_x && y == target_y {
+                return true;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+            if (x != target_x || y != target_y) && self.blocks_sight(Vec2::new(x as u16, y as u16)) {
+                return false;
+            }
+        }
+    }
+
+    fn blocks_sight(&self, point: Vec2) -> bool {
+        self.rect.contains(point) && self.sight_blocking[self.index(point)]
+    }
+
+    fn index(&self, point: Vec2) -> usize {
+        (point.y - self.rect.min.y) as usize * self.width + (point.x - self.rect.min.x) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::world::types::MapTileV1;
+
+    fn grassland(viewer: Vec3) -> VisibilityGrid {
+        let mut grid = VisibilityGrid::new(viewer, 8);
+        grid.add_tiles(grid.rect(), viewer.z, &MapTileStackV1::new(MapTileV1::Grass));
+        grid
+    }
+
+    #[test]
+    fn open_ground_is_fully_visible_on_the_same_floor() {
+        let grid = grassland(Vec3::new(100, 100, GROUND_LEVEL));
+
+        assert!(grid.can_see(Vec3::new(100, 100, GROUND_LEVEL)));
+        assert!(grid.can_see(Vec3::new(108, 92, GROUND_LEVEL)));
+        assert!(!grid.can_see(Vec3::new(109, 100, GROUND_LEVEL)));
+    }
+
+    #[test]
+    fn walls_hide_what_is_behind_them() {
+        let mut grid = grassland(Vec3::new(100, 100, GROUND_LEVEL));
+        grid.add_tiles(
+            Rect::new(103, 95, 103, 105),
+            GROUND_LEVEL,
+            &MapTileStackV1::new(MapTileV1::Grass).with_layer(MapTileV1::Wall),
+        );
+
+        assert!(!grid.can_see(Vec3::new(106, 100, GROUND_LEVEL)));
+        assert!(!grid.can_see(Vec3::new(105, 98, GROUND_LEVEL)));
+        assert!(grid.can_see(Vec3::new(103, 100, GROUND_LEVEL)));
+        assert!(grid.can_see(Vec3::new(100, 106, GROUND_LEVEL)));
+    }
+
+    #[test]
+    fn lower_floor_is_seen_only_through_openings() {
+        let mut grid = VisibilityGrid::new(Vec3::new(100, 100, GROUND_LEVEL), 8);
+        grid.add_tiles(
+            Rect::new(92, 92, 108, 104),
+            GROUND_LEVEL,
+            &MapTileStackV1::new(MapTileV1::Grass),
+        );
+        grid.add_tiles(
+            Rect::new(102, 100, 102, 100),
+            GROUND_LEVEL,
+            &MapTileStackV1::new(MapTileV1::Dirt).with_layer(MapTileV1::Hole),
+        );
+
+        assert!(grid.can_see(Vec3::new(102, 100, GROUND_LEVEL - 1)));
+        assert!(!grid.can_see(Vec3::new(101, 100, GROUND_LEVEL - 1)));
+        assert!(!grid.can_see(Vec3::new(100, 107, GROUND_LEVEL - 1)));
+        assert!(grid.reveals_below(Rect::new(90, 90, 102, 100)));
+        assert!(!grid.reveals_below(Rect::new(90, 90, 101, 110)));
+    }
+
+    #[test]
+    fn uncovered_tiles_open_the_view_below_but_never_above() {
+        let mut grid = VisibilityGrid::new(Vec3::new(100, 100, GROUND_LEVEL + 1), 8);
+        grid.add_tiles(
+            Rect::new(98, 98, 102, 102),
+            GROUND_LEVEL + 1,
+            &MapTileStackV1::new(MapTileV1::StoneFloor),
+        );
+
+        assert!(!grid.can_see(Vec3::new(100, 101, GROUND_LEVEL)));
+        assert!(grid.can_see(Vec3::new(105, 100, GROUND_LEVEL)));
+        assert!(!grid.can_see(Vec3::new(100, 101, GROUND_LEVEL + 2)));
+        assert!(!grid.can_see(Vec3::new(105, 100, GROUND_LEVEL - 1)));
+        assert_eq!(grid.floors(), GROUND_LEVEL..=GROUND_LEVEL + 1);
+    }
+
+    #[test]
+    fn sight_radius_limits_entities_but_not_the_grid() {
+        let grid = grassland(Vec3::new(100, 100, GROUND_LEVEL)).with_sight_radius(3);
+
+        assert!(grid.can_see(Vec3::new(103, 97, GROUND_LEVEL)));
+        assert!(!grid.can_see(Vec3::new(104, 100, GROUND_LEVEL)));
+        assert_eq!(grid.rect(), Rect::new(92, 92, 108, 108));
+    }
+}
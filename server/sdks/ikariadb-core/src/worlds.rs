@@ -0,0 +1,43 @@
+//! Settings of every world binary, kept in one place so tools such as `map-render` see the map each world seeds.
+
+use crate::{
+    config::{MapSource, WorldConfig},
+    repository::world::{
+        terrain::TerrainSettings,
+        types::{PvpModeV1, Vec2},
+    },
+};
+use ikaria_shared::constants::GROUND_LEVEL;
+
+pub const ALPHA_TERRAIN: TerrainSettings = TerrainSettings {
+    seed: 0xA1FA,
+    origin: Vec2 { x: 896, y: 896 },
+    width: 512,
+    height: 512,
+    z: GROUND_LEVEL,
+};
+
+pub const WORLD_NAMES: [&str; 2] = ["alpha", "draconis"];
+
+pub fn alpha() -> WorldConfig {
+    WorldConfig {
+        map_source: MapSource::Procedural(ALPHA_TERRAIN),
+        pvp_mode: PvpModeV1::Optional,
+        ..WorldConfig::default()
+    }
+}
+
+pub fn draconis() -> WorldConfig {
+    WorldConfig {
+        pvp_mode: PvpModeV1::Open,
+        ..WorldConfig::default()
+    }
+}
+
+pub fn by_name(name: &str) -> Option<WorldConfig> {
+    match name {
+        "alpha" => Some(alpha()),
+        "draconis" => Some(draconis()),
+        _ => None,
+    }
+}